@@ -0,0 +1,77 @@
+//! Packaging step for the embedded-interpreter mode (see `python::embedded`)
+//!
+//! When `XC_EMBED_PYTHON_STDLIB` points at a Python standard library (or a
+//! project's dependencies) this walks the tree, gathers the source of every
+//! `.py` module, and writes `$OUT_DIR/embedded_stdlib.rs`, a static table of
+//! `(module_name, source)` pairs compiled into the `xc` binary. Modules are
+//! embedded as source rather than bytecode so they are compiled by whichever
+//! CPython `xc` is actually running against, guaranteeing a version match.
+
+use std::{env, fs, path::Path};
+
+fn main() {
+  let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+  let destination = Path::new(&out_dir).join("embedded_stdlib.rs");
+
+  println!("cargo:rerun-if-env-changed=XC_EMBED_PYTHON_STDLIB");
+
+  let Ok(stdlib_root) = env::var("XC_EMBED_PYTHON_STDLIB") else {
+    fs::write(&destination, "pub const EMBEDDED_MODULES: &[(&str, &str)] = &[];\n")
+      .expect("can write empty embedded module table");
+    return;
+  };
+
+  println!("cargo:rerun-if-changed={stdlib_root}");
+
+  let mut modules = Vec::new();
+  collect_modules(Path::new(&stdlib_root), Path::new(&stdlib_root), &mut modules);
+
+  let mut source = String::from("pub const EMBEDDED_MODULES: &[(&str, &str)] = &[\n");
+  for (name, contents) in &modules {
+    source.push_str(&format!("  ({name:?}, {contents:?}),\n"));
+  }
+  source.push_str("];\n");
+
+  fs::write(&destination, source).expect("can write embedded module table");
+}
+
+/// Recursively collects `(dotted.module.name, source)` pairs from `.py` files
+fn collect_modules(root: &Path, dir: &Path, modules: &mut Vec<(String, String)>) {
+  let Ok(entries) = fs::read_dir(dir) else {
+    return;
+  };
+
+  for entry in entries.filter_map(Result::ok) {
+    let path = entry.path();
+
+    if path.is_dir() {
+      collect_modules(root, &path, modules);
+      continue;
+    }
+
+    if path.extension().is_some_and(|extension| extension == "py") {
+      let Ok(contents) = fs::read_to_string(&path) else {
+        continue;
+      };
+      let Ok(relative) = path.strip_prefix(root) else {
+        continue;
+      };
+
+      let mut module_name = relative.with_extension("");
+      let is_package_init = module_name.file_name().is_some_and(|name| name == "__init__");
+      if is_package_init {
+        module_name.pop();
+      }
+
+      let name = module_name
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(".");
+
+      if !name.is_empty() {
+        modules.push((name, contents));
+      }
+    }
+  }
+}
@@ -1,6 +1,6 @@
 use clap::Parser;
 
-#[derive(Parser, Debug, Default)]
+#[derive(Parser, Clone, Debug, Default)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Settings {
   /// List of files or directories to test
@@ -14,16 +14,63 @@ pub(crate) struct Settings {
   #[clap(flatten)]
   pub coverage: CoverageSettings,
 
+  #[clap(flatten)]
+  pub fuzz: FuzzSettings,
+
+  /// Track and report peak memory usage per test
+  #[clap(long = "memory-profile", default_value_t = false)]
+  pub memory_profile: bool,
+
+  /// Resolve the standard library from the copy bundled in this binary, rather than a
+  /// system Python, if `xc` was built with `XC_EMBED_PYTHON_STDLIB`
+  #[clap(long = "embedded-stdlib", default_value_t = false)]
+  pub embedded_stdlib: bool,
+
   /// Don't stop executing tests after one has failed
   #[clap(long, default_value_t = false)]
   pub no_fail_fast: bool,
 
+  /// Keep running, re-discovering and re-running tests whenever a source file changes
+  #[clap(long, default_value_t = false)]
+  pub watch: bool,
+
+  /// Discover and run `>>>` examples embedded in docstrings
+  #[clap(long, default_value_t = false)]
+  pub doctest: bool,
+
+  /// Run tests in a shuffled order, to surface order-dependent flakiness
+  #[clap(long, default_value_t = false)]
+  pub shuffle: bool,
+
+  /// Seed used to shuffle the test order; if not given, a random seed is generated and printed
+  #[clap(long = "shuffle-seed", value_name = "SEED")]
+  pub shuffle_seed: Option<u64>,
+
+  /// Only run tests whose name matches this substring, or - wrapped in `/.../` - this regex
+  #[clap(long, value_name = "PATTERN")]
+  pub filter: Option<String>,
+
+  /// Interrupt a file's tests if they run for longer than this many milliseconds in total;
+  /// the limit applies to each file's group of tests as a whole, not to each test on its own
+  #[clap(long, value_name = "MS")]
+  pub timeout: Option<u64>,
+
+  /// Re-run a failing test up to this many times before declaring it failed, reporting it
+  /// as `Flaky` rather than `Fail` if a re-run passes
+  #[clap(long, value_name = "COUNT", default_value_t = 0)]
+  pub retries: u32,
+
   /// How test results should be reported
   #[clap(long, value_enum, default_value_t = OutputFormat::Standard)]
   pub output: OutputFormat,
+
+  /// Path to a TOML manifest marking known-broken tests as expected to fail or error,
+  /// so the suite can still exit successfully while unexpected passes are flagged
+  #[clap(long, value_name = "PATH")]
+  pub expectations: Option<std::path::PathBuf>,
 }
 
-#[derive(clap::Args, Debug, Default)]
+#[derive(clap::Args, Clone, Debug, Default)]
 pub(crate) struct CoverageSettings {
   /// Enable line coverage gathering and reporting
   #[clap(long = "coverage", default_value_t = false)]
@@ -46,6 +93,58 @@ pub(crate) struct CoverageSettings {
     help_heading = "Coverage"
   )]
   pub exclude: Vec<std::path::PathBuf>,
+
+  /// Write coverage results as an LCOV tracefile to the given path
+  #[clap(
+    name = "coverage-lcov",
+    long = "coverage-lcov",
+    value_name = "PATH",
+    help_heading = "Coverage"
+  )]
+  pub lcov: Option<std::path::PathBuf>,
+
+  /// Write coverage results as a Cobertura XML report to the given path
+  #[clap(
+    name = "coverage-cobertura",
+    long = "coverage-cobertura",
+    value_name = "PATH",
+    help_heading = "Coverage"
+  )]
+  pub cobertura: Option<std::path::PathBuf>,
+
+  /// Omit files with no missed lines from the printed coverage summary
+  #[clap(
+    name = "coverage-skip-covered",
+    long = "coverage-skip-covered",
+    default_value_t = false,
+    help_heading = "Coverage"
+  )]
+  pub skip_covered: bool,
+
+  /// Fail if the aggregate or any individual file's coverage is below this percentage
+  #[clap(
+    name = "cov-fail-under",
+    long = "cov-fail-under",
+    value_name = "PERCENTAGE",
+    help_heading = "Coverage"
+  )]
+  pub fail_under: Option<f64>,
+}
+
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(crate) struct FuzzSettings {
+  /// Enable coverage-guided fuzzing of parametrized test functions
+  #[clap(long = "fuzz", default_value_t = false, help_heading = "Fuzz")]
+  pub enabled: bool,
+
+  /// Maximum number of mutated inputs to try per fuzzed test
+  #[clap(
+    long = "fuzz-iterations",
+    value_name = "COUNT",
+    default_value_t = 10_000,
+    help_heading = "Fuzz"
+  )]
+  pub iterations: u32,
 }
 
 #[derive(Copy, Clone, Default, Debug, clap::ValueEnum)]
@@ -55,6 +154,10 @@ pub(crate) enum OutputFormat {
   Standard,
   /// Output each test as a JSON object on a new line
   Json,
+  /// Output a single JUnit XML document, for ingestion by CI dashboards
+  Junit,
+  /// Emit GitHub Actions workflow commands, annotating failures inline on the PR diff
+  GitHub,
 }
 
 /// Reads settings from command line arguments and `pyproject.toml`
@@ -67,6 +170,13 @@ pub fn read_settings() -> Settings {
     }
   }
 
+  // Auto-select GitHub Actions annotations when running in a workflow and the user hasn't
+  // asked for a different format themselves
+  if matches!(settings.output, OutputFormat::Standard) && std::env::var_os("GITHUB_ACTIONS").is_some()
+  {
+    settings.output = OutputFormat::GitHub;
+  }
+
   settings
 }
 
@@ -95,6 +205,7 @@ mod pyproject_toml {
     coverage: Option<bool>,
     coverage_include: Option<Vec<PathBuf>>,
     coverage_exclude: Option<Vec<PathBuf>>,
+    filter: Option<String>,
   }
 
   /// Get the path to a `pyproject.toml` file, if one exists in the current tree
@@ -160,5 +271,11 @@ mod pyproject_toml {
         settings.coverage.exclude = mem::take(coverage_exclude);
       }
     }
+
+    if let Some(filter) = toml_config.filter.take() {
+      if settings.filter.is_none() {
+        settings.filter = Some(filter);
+      }
+    }
   }
 }
@@ -4,7 +4,7 @@
 use rayon::prelude::*;
 use std::collections::{BTreeSet, HashMap};
 use std::ffi::CString;
-use std::{fs, path};
+use std::{fs, io, path};
 
 use crate::python::{
   objects::{PyDict, PyError, PyIter, PyObject, PyTuple},
@@ -18,24 +18,24 @@ pub fn enable_collection(python: &ActiveInterpreter) -> PyObject {
   let module = python.execute_string(&source).expect("code to run");
 
   module
-    .get_attr(&python.new_string("tracer"))
+    .get_attr(python, &python.new_string("tracer"))
     .expect("`tracer` var to exist")
 }
 
 /// Get the lines that have been executed, converting them from a Python structure
-pub fn get_executed_lines(_python: &ActiveInterpreter, tracer_object: &PyObject) -> Lines {
+pub fn get_executed_lines(python: &ActiveInterpreter, tracer_object: &PyObject) -> Lines {
   let lines = tracer_object.get_attr_cstr(c"lines").unwrap();
   let filename_line_pairs = PyDict::from_object(lines).unwrap().items();
 
   filename_line_pairs
-    .into_iter()
+    .into_iter(python)
     .map(|tuple| {
       let tuple = unsafe { PyTuple::from_object_unchecked(tuple) };
       let filename = unsafe { tuple.get_item_unchecked(0).to_string() };
       let lines_set = unsafe { tuple.get_item_unchecked(1) };
 
       let lines = lines_set
-        .into_iter()
+        .into_iter(python)
         .map(|line_no| line_no.as_long())
         .collect();
 
@@ -59,6 +59,13 @@ impl Lines {
   pub fn iter(&self) -> impl Iterator<Item = (&String, &BTreeSet<i32>)> {
     self.0.iter()
   }
+
+  /// Merges another set of lines into this one, unioning the lines for shared files
+  pub fn merge(&mut self, other: Lines) {
+    for (file, lines) in other.0 {
+      self.0.entry(file).or_default().extend(lines);
+    }
+  }
 }
 // Merge together the executed line information
 impl ParallelExtend<Option<Lines>> for Lines {
@@ -179,8 +186,8 @@ fn get_line_numbers_from_code_object(
   line_numbers: &mut BTreeSet<i32>,
 ) -> Result<(), PyError> {
   // Search all constants for code objects and recurse into them
-  let constants = code_object.get_attr(&python.new_string("co_consts"))?;
-  let code_objects = constants.into_iter().filter(PyObject::is_code_object);
+  let constants = code_object.get_attr(python, &python.new_string("co_consts"))?;
+  let code_objects = constants.into_iter(python).filter(PyObject::is_code_object);
   for code_object in code_objects {
     get_line_numbers_from_code_object(python, &code_object, line_numbers)?;
   }
@@ -206,7 +213,175 @@ fn get_line_numbers_from_code_object(
   Ok(())
 }
 
-pub fn print_summary(possible: &Lines, executed: &Lines) {
+/// Writes the coverage results to `path` as an LCOV tracefile
+///
+/// One `SF`/`DA`/`LF`/`LH`/`end_of_record` record is written per file with executable lines,
+/// so the result can be fed into existing LCOV-consuming coverage tooling (e.g. `genhtml`)
+pub fn write_lcov(path: &path::Path, possible: &Lines, executed: &Lines) -> io::Result<()> {
+  let mut output = String::new();
+  let empty = BTreeSet::new();
+
+  for (file_name, possible_lines) in possible.iter() {
+    let executed_lines = executed.get_lines(file_name).unwrap_or(&empty);
+
+    output.push_str("SF:");
+    output.push_str(file_name);
+    output.push('\n');
+
+    for line in possible_lines {
+      let hits = i32::from(executed_lines.contains(line));
+      output.push_str(&format!("DA:{line},{hits}\n"));
+    }
+
+    output.push_str(&format!("LF:{}\n", possible_lines.len()));
+    output.push_str(&format!(
+      "LH:{}\n",
+      possible_lines.intersection(executed_lines).count()
+    ));
+    output.push_str("end_of_record\n");
+  }
+
+  fs::write(path, output)
+}
+
+/// Writes the coverage results to `path` as a Cobertura XML report
+///
+/// One `<class>` is written per file with executable lines, each holding a `<line>` per
+/// reachable line number with its hit count, so the result can be fed into CI tooling that
+/// consumes Cobertura (e.g. Codecov, Jenkins)
+pub fn write_cobertura(path: &path::Path, possible: &Lines, executed: &Lines) -> io::Result<()> {
+  use std::fmt::Write;
+
+  let mut output = String::new();
+  let empty = BTreeSet::new();
+
+  let total_lines: usize = possible.iter().map(|(_, lines)| lines.len()).sum();
+  let covered_lines: usize = possible
+    .iter()
+    .map(|(file_name, lines)| {
+      let executed_lines = executed.get_lines(file_name).unwrap_or(&empty);
+      lines.intersection(executed_lines).count()
+    })
+    .sum();
+
+  #[expect(clippy::cast_precision_loss, reason = "line numbers < f64::MAX")]
+  let line_rate = if total_lines == 0 {
+    0.0
+  } else {
+    covered_lines as f64 / total_lines as f64
+  };
+
+  writeln!(output, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+  writeln!(
+    output,
+    "<coverage line-rate=\"{line_rate:.4}\" lines-covered=\"{covered_lines}\" \
+     lines-valid=\"{total_lines}\">"
+  )
+  .unwrap();
+  writeln!(output, "  <packages>").unwrap();
+  writeln!(output, "    <package name=\"\" line-rate=\"{line_rate:.4}\">").unwrap();
+  writeln!(output, "      <classes>").unwrap();
+
+  for (file_name, possible_lines) in possible.iter() {
+    let executed_lines = executed.get_lines(file_name).unwrap_or(&empty);
+    let file_covered = possible_lines.intersection(executed_lines).count();
+
+    #[expect(clippy::cast_precision_loss, reason = "line numbers < f64::MAX")]
+    let file_line_rate = file_covered as f64 / possible_lines.len() as f64;
+
+    let escaped_file_name = escape_xml_attribute(file_name);
+    writeln!(
+      output,
+      "        <class name=\"{escaped_file_name}\" filename=\"{escaped_file_name}\" line-rate=\"{file_line_rate:.4}\">"
+    )
+    .unwrap();
+    writeln!(output, "          <lines>").unwrap();
+    for line in possible_lines {
+      let hits = i32::from(executed_lines.contains(line));
+      writeln!(output, "            <line number=\"{line}\" hits=\"{hits}\"/>").unwrap();
+    }
+    writeln!(output, "          </lines>").unwrap();
+    writeln!(output, "        </class>").unwrap();
+  }
+
+  writeln!(output, "      </classes>").unwrap();
+  writeln!(output, "    </package>").unwrap();
+  writeln!(output, "  </packages>").unwrap();
+  writeln!(output, "</coverage>").unwrap();
+
+  fs::write(path, output)
+}
+
+/// Escapes the characters XML treats as special, so an arbitrary file path can be embedded
+/// as an attribute value without producing malformed XML
+fn escape_xml_attribute(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+/// Checks the aggregate and per-file coverage against `threshold`, a required percentage
+///
+/// Prints the files that fell short before returning whether the gate was met, so CI can
+/// enforce a coverage floor rather than treating the summary as purely advisory
+pub fn check_fail_under(possible: &Lines, executed: &Lines, threshold: f64) -> bool {
+  use anstream::eprintln;
+  use owo_colors::OwoColorize;
+
+  let empty = BTreeSet::new();
+
+  let total_lines: usize = possible.iter().map(|(_, lines)| lines.len()).sum();
+  let covered_lines: usize = possible
+    .iter()
+    .map(|(file_name, lines)| {
+      let executed_lines = executed.get_lines(file_name).unwrap_or(&empty);
+      lines.intersection(executed_lines).count()
+    })
+    .sum();
+
+  #[expect(clippy::cast_precision_loss, reason = "line numbers < f64::MAX")]
+  let aggregate_coverage = if total_lines == 0 {
+    100.0
+  } else {
+    (covered_lines as f64 / total_lines as f64) * 100.0
+  };
+
+  let shortfalls: Vec<(&String, f64)> = possible
+    .iter()
+    .filter_map(|(file_name, possible_lines)| {
+      let executed_lines = executed.get_lines(file_name).unwrap_or(&empty);
+      let covered = possible_lines.intersection(executed_lines).count();
+
+      #[expect(clippy::cast_precision_loss, reason = "line numbers < f64::MAX")]
+      let file_coverage = (covered as f64 / possible_lines.len() as f64) * 100.0;
+
+      (file_coverage < threshold).then_some((file_name, file_coverage))
+    })
+    .collect();
+
+  let passed = aggregate_coverage >= threshold && shortfalls.is_empty();
+
+  if !passed {
+    eprintln!(
+      "\n{} {:.1}% {} {:.1}%",
+      "Coverage".bold().red(),
+      aggregate_coverage,
+      "is below the required".red(),
+      threshold
+    );
+
+    for (file_name, file_coverage) in &shortfalls {
+      eprintln!("{}{file_name}: {file_coverage:.1}%", "├─ ".dimmed());
+    }
+  }
+
+  passed
+}
+
+pub fn print_summary(possible: &Lines, executed: &Lines, skip_covered: bool) {
   use anstream::eprintln;
   use owo_colors::OwoColorize;
 
@@ -217,7 +392,7 @@ pub fn print_summary(possible: &Lines, executed: &Lines) {
     "{}{:55} {}",
     "│  ".dimmed(),
     "File".dimmed().italic(),
-    "Lines    Missed  Coverage".dimmed().italic(),
+    "Lines    Missed  Coverage  Missing".dimmed().italic(),
   );
 
   for (file_name, possible_lines) in possible.iter() {
@@ -226,17 +401,93 @@ pub fn print_summary(possible: &Lines, executed: &Lines) {
     let total_lines = possible_lines.len();
     let missed_lines = total_lines - covered_lines;
 
+    if skip_covered && missed_lines == 0 {
+      continue;
+    }
+
     #[expect(clippy::cast_precision_loss, reason = "line numbers < f64::MAX")]
     let coverage = (covered_lines as f64 / total_lines as f64) * 100.0;
 
+    let missing: BTreeSet<i32> = possible_lines.difference(executed_lines).copied().collect();
+
     eprintln!(
-      "{}{:55}{:6}{:>10}{:>9.1}%",
+      "{}{:55}{:6}{:>10}{:>9.1}%  {}",
       "├─ ".dimmed(),
       file_name,
       total_lines,
       missed_lines,
       coverage,
+      format_missing_ranges(&missing),
     );
   }
   eprintln!("{}", "╰──".dimmed());
 }
+
+/// Collapses a sorted set of line numbers into a compact list of ranges (e.g. `12-19, 24, 88-90`)
+fn format_missing_ranges(missing: &BTreeSet<i32>) -> String {
+  let mut ranges = Vec::new();
+  let mut lines = missing.iter().copied();
+
+  let Some(mut start) = lines.next() else {
+    return String::new();
+  };
+  let mut end = start;
+
+  for line in lines {
+    if line == end + 1 {
+      end = line;
+    } else {
+      ranges.push(format_range(start, end));
+      start = line;
+      end = line;
+    }
+  }
+  ranges.push(format_range(start, end));
+
+  ranges.join(", ")
+}
+
+fn format_range(start: i32, end: i32) -> String {
+  if start == end {
+    start.to_string()
+  } else {
+    format!("{start}-{end}")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{escape_xml_attribute, format_missing_ranges};
+  use std::collections::BTreeSet;
+
+  #[test]
+  fn empty_set_formats_as_an_empty_string() {
+    assert_eq!(format_missing_ranges(&BTreeSet::new()), "");
+  }
+
+  #[test]
+  fn single_line_formats_without_a_range() {
+    assert_eq!(format_missing_ranges(&BTreeSet::from([12])), "12");
+  }
+
+  #[test]
+  fn consecutive_lines_collapse_into_a_range() {
+    assert_eq!(format_missing_ranges(&BTreeSet::from([12, 13, 14])), "12-14");
+  }
+
+  #[test]
+  fn non_consecutive_lines_are_listed_separately() {
+    assert_eq!(
+      format_missing_ranges(&BTreeSet::from([12, 13, 14, 24, 88, 89, 90])),
+      "12-14, 24, 88-90"
+    );
+  }
+
+  #[test]
+  fn escape_xml_attribute_escapes_all_xml_special_characters() {
+    assert_eq!(
+      escape_xml_attribute(r#"<tag a="b" c='d'> & text"#),
+      "&lt;tag a=&quot;b&quot; c=&apos;d&apos;&gt; &amp; text"
+    );
+  }
+}
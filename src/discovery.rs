@@ -1,5 +1,7 @@
 //! Discover tests in Python files so they can be run later
 
+use crate::python::{self, objects::PyTuple, ActiveInterpreter, Interpreter, MainInterpreter};
+
 use std::{
   fs,
   num::NonZero,
@@ -13,7 +15,15 @@ use std::{
 #[derive(Debug, Clone)]
 pub enum Test {
   /// A test which is a function
-  Function { file: PathBuf, function: String },
+  Function {
+    file: PathBuf,
+    function: String,
+    /// The function's declared parameters, in order, with the type their annotation names.
+    ///
+    /// Non-empty for a fuzz target (e.g. a function decorated with `@fuzz`,
+    /// or any test function which takes arguments).
+    parameters: Vec<FuzzParameter>,
+  },
 
   /// A test which is a method on a class
   Method {
@@ -21,19 +31,38 @@ pub enum Test {
     class: String,
     method: String,
   },
+
+  /// A `>>>` example extracted from a module, class, or function docstring
+  DocTest {
+    file: PathBuf,
+    /// The dotted name of the docstring owner, as reported by `doctest.DocTest.name`
+    qualified_name: String,
+    /// The line the docstring starts on, as reported by `doctest.DocTest.lineno`
+    line: i32,
+  },
 }
 impl Test {
   /// Get the file of the test
   pub fn file(&self) -> &Path {
     match self {
-      Test::Function { file, .. } | Test::Method { file, .. } => file,
+      Test::Function { file, .. } | Test::Method { file, .. } | Test::DocTest { file, .. } => {
+        file
+      }
+    }
+  }
+
+  /// Get the declared parameters of the test, if it is a parametrized fuzz target
+  pub fn parameters(&self) -> &[FuzzParameter] {
+    match self {
+      Test::Function { parameters, .. } => parameters,
+      Test::Method { .. } | Test::DocTest { .. } => &[],
     }
   }
 
   /// Get the suite of the test
   pub fn suite(&self) -> Option<&str> {
     match self {
-      Test::Function { .. } => None,
+      Test::Function { .. } | Test::DocTest { .. } => None,
       Test::Method { class, .. } => Some(class),
     }
   }
@@ -43,11 +72,21 @@ impl Test {
     match self {
       Test::Function { function, .. } => function,
       Test::Method { method, .. } => method,
+      Test::DocTest { qualified_name, .. } => qualified_name,
     }
   }
 
   /// Get the name and suite of the test combined into a single identifier
   pub fn identifier(&self) -> String {
+    if let Test::DocTest {
+      qualified_name,
+      line,
+      ..
+    } = self
+    {
+      return format!("{qualified_name} (line {line})");
+    }
+
     let mut identifier = String::new();
     if let Some(suite) = self.suite() {
       identifier.push_str(suite);
@@ -58,6 +97,39 @@ impl Test {
   }
 }
 
+/// A fuzz target's declared parameter - its name, and the type [`crate::fuzz`] should decode
+/// mutated bytes into, taken from the parameter's type annotation if it has one
+#[derive(Debug, Clone)]
+pub struct FuzzParameter {
+  pub name: String,
+  pub kind: FuzzParameterKind,
+}
+
+/// The types [`crate::fuzz::decode_arguments`] knows how to decode a mutated byte chunk into
+#[derive(Debug, Clone, Copy, Default)]
+pub enum FuzzParameterKind {
+  /// No annotation, or one that isn't recognised - decoded as an `i64`
+  #[default]
+  Int,
+  Float,
+  Bool,
+  Str,
+  Bytes,
+}
+impl FuzzParameterKind {
+  /// Maps an annotation's name (e.g. the `int` of `x: int`) to the type it names,
+  /// falling back to [`Self::Int`] for anything not recognised
+  fn from_annotation_name(name: &str) -> Self {
+    match name {
+      "float" => Self::Float,
+      "bool" => Self::Bool,
+      "str" => Self::Str,
+      "bytes" => Self::Bytes,
+      _ => Self::Int,
+    }
+  }
+}
+
 /// Holds the tests discovered, and metadata about the discovery
 pub struct DiscoveredTests {
   start: Instant,
@@ -69,6 +141,10 @@ pub struct DiscoveredTests {
   pub test_count: usize,
   /// How many files were the tests found in
   pub file_count: usize,
+  /// The seed tests were shuffled with, if `--shuffle` was passed
+  pub shuffle_seed: Option<u64>,
+  /// How many tests were found before `--filter` narrowed them down, if one was passed
+  pub filtered_count: Option<usize>,
 }
 impl DiscoveredTests {
   fn new() -> Self {
@@ -78,6 +154,8 @@ impl DiscoveredTests {
       tests: Vec::new(),
       file_count: 0,
       test_count: 0,
+      shuffle_seed: None,
+      filtered_count: None,
     }
   }
 }
@@ -108,6 +186,85 @@ pub fn find_tests(paths: &[PathBuf]) -> DiscoveredTests {
   state.into_inner().unwrap()
 }
 
+/// Finds `>>>` doctest examples in the given paths, using a live interpreter
+///
+/// Unlike [`find_tests`], this needs to actually import each candidate module (to read its
+/// docstrings via `doctest.DocTestFinder`), so it must run after a [`MainInterpreter`] has
+/// been initialized, rather than as part of the purely syntactic discovery pass
+pub fn find_doctests(
+  interpreter: &MainInterpreter,
+  paths: &[PathBuf],
+  exclude: &[PathBuf],
+) -> Vec<Test> {
+  let (first_path, rest_paths) = paths.split_first().expect("at least one path to search");
+
+  let mut builder = ignore::WalkBuilder::new(first_path);
+  for path in rest_paths {
+    builder.add(path);
+  }
+  builder.standard_filters(true);
+
+  let mut exclude_override = ignore::overrides::OverrideBuilder::new("");
+  for path in exclude {
+    exclude_override
+      .add(&format!("!{}", path.to_string_lossy()))
+      .unwrap();
+  }
+  builder.overrides(exclude_override.build().unwrap());
+
+  builder
+    .build()
+    .filter_map(Result::ok)
+    .map(ignore::DirEntry::into_path)
+    .filter(|path| path.is_file() && path.extension().unwrap_or_default() == "py")
+    .flat_map(|path| {
+      python::SubInterpreter::new(interpreter)
+        .with_gil(|python| find_doctests_in_file(python, &path))
+    })
+    .collect()
+}
+
+/// Imports `file` as a module and enumerates its doctests via `doctest.DocTestFinder`
+fn find_doctests_in_file(python: &ActiveInterpreter, file: &Path) -> Vec<Test> {
+  let Ok(module) = python.execute_file(file) else {
+    return Vec::new();
+  };
+
+  let doctest_module = python.import_module(c"doctest");
+  let Ok(finder) = doctest_module
+    .get_attr(python, &python.new_string("DocTestFinder"))
+    .and_then(|finder| finder.call(python))
+  else {
+    return Vec::new();
+  };
+  let Ok(found) = finder
+    .get_attr(python, &python.new_string("find"))
+    .and_then(|find| find.call_with_args(&PyTuple::new(vec![module])))
+  else {
+    return Vec::new();
+  };
+
+  found
+    .into_iter(python)
+    // Skip docstrings with no `>>>` examples, same as `doctest.testmod`'s default behaviour
+    .filter(|doctest| {
+      doctest
+        .get_attr_cstr(c"examples")
+        .is_ok_and(|examples| examples.into_iter(python).next().is_some())
+    })
+    .filter_map(|doctest| {
+      let qualified_name = doctest.get_attr_cstr(c"name").ok()?.to_string();
+      let line = doctest.get_attr_cstr(c"lineno").ok()?.as_long();
+
+      Some(Test::DocTest {
+        file: file.to_path_buf(),
+        qualified_name,
+        line,
+      })
+    })
+    .collect()
+}
+
 /// Find any tests in a given file.
 ///
 /// - Parse the file as Python
@@ -126,9 +283,27 @@ fn get_test_methods(file: &Path, tests: &mut Vec<Test>) {
       let name = &function_def.name;
 
       if name.starts_with("test") {
+        let parameters = function_def
+          .parameters
+          .iter_non_variadic_params()
+          .map(|parameter| &parameter.parameter)
+          .filter(|parameter| parameter.name.as_str() != "self")
+          .map(|parameter| FuzzParameter {
+            name: parameter.name.to_string(),
+            kind: parameter
+              .annotation
+              .as_deref()
+              .and_then(|annotation| annotation.as_name_expr())
+              .map_or(FuzzParameterKind::Int, |name| {
+                FuzzParameterKind::from_annotation_name(name.id.as_str())
+              }),
+          })
+          .collect();
+
         tests.push(Test::Function {
           file: file.into(),
           function: name.to_string(),
+          parameters,
         });
       }
     }
@@ -0,0 +1,51 @@
+//! # Expectations
+//! Reconciles test outcomes against an external manifest of known-broken tests, so a suite
+//! with known failures can still exit green in CI while still flagging genuinely new breakage
+
+use serde::Deserialize;
+use std::{collections::HashMap, fs, path::Path};
+
+/// Maps test identifiers to the outcome they are expected to have
+#[derive(Debug, Default)]
+pub struct Expectations(HashMap<String, RequiredOutcome>);
+impl Expectations {
+  /// Reads an expectations manifest (e.g. `xc-expectations.toml`) from `path`
+  pub fn read(path: &Path) -> Self {
+    let contents = fs::read_to_string(path).expect("expectations file to be readable");
+    let file: ExpectationsFile =
+      toml::from_str(&contents).expect("expectations file to be valid toml");
+
+    Self(
+      file
+        .busted
+        .into_iter()
+        .map(|entry| (entry.test_identifier, entry.outcome))
+        .collect(),
+    )
+  }
+
+  /// The outcome `test_identifier` is expected to have, if it is listed as busted
+  pub fn get(&self, test_identifier: &str) -> Option<RequiredOutcome> {
+    self.0.get(test_identifier).copied()
+  }
+}
+
+#[derive(Deserialize)]
+struct ExpectationsFile {
+  #[serde(default)]
+  busted: Vec<BustedEntry>,
+}
+
+#[derive(Deserialize)]
+struct BustedEntry {
+  test_identifier: String,
+  outcome: RequiredOutcome,
+}
+
+/// The outcome a busted test is required to have for its manifest entry to be satisfied
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequiredOutcome {
+  Fail,
+  Error,
+}
@@ -0,0 +1,76 @@
+//! Select a subset of discovered tests by matching `--filter` against [`Test::identifier`]
+
+use crate::discovery::Test;
+
+/// A parsed `--filter` value: either a plain substring, or - when wrapped in `/.../` - a
+/// compiled regular expression
+pub enum Filter {
+  Substring(String),
+  Regex(regex::Regex),
+}
+impl Filter {
+  /// Parses a `--filter` value.
+  ///
+  /// A value wrapped in `/.../` is compiled as a regex; anything else (including an
+  /// unterminated or invalid `/.../`) is matched as a plain substring
+  pub fn parse(value: &str) -> Self {
+    if let Some(pattern) = value.strip_prefix('/').and_then(|rest| rest.strip_suffix('/')) {
+      if let Ok(regex) = regex::Regex::new(pattern) {
+        return Self::Regex(regex);
+      }
+    }
+
+    Self::Substring(value.to_string())
+  }
+
+  fn matches(&self, identifier: &str) -> bool {
+    match self {
+      Self::Substring(substring) => identifier.contains(substring.as_str()),
+      Self::Regex(regex) => regex.is_match(identifier),
+    }
+  }
+}
+
+/// Keeps only the tests whose [`Test::identifier`] matches `filter`
+pub fn apply(tests: &mut Vec<Test>, filter: &Filter) {
+  tests.retain(|test| filter.matches(&test.identifier()));
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Filter;
+
+  #[test]
+  fn substring_matches_anywhere_in_the_identifier() {
+    let filter = Filter::parse("Suite.test_add");
+
+    assert!(filter.matches("TestSuite.test_add"));
+    assert!(!filter.matches("TestSuite.test_subtract"));
+  }
+
+  #[test]
+  fn regex_wrapped_in_slashes_is_compiled_and_matched() {
+    let filter = Filter::parse("/^test_(add|subtract)$/");
+
+    assert!(filter.matches("test_add"));
+    assert!(filter.matches("test_subtract"));
+    assert!(!filter.matches("test_multiply"));
+    assert!(!filter.matches("not_test_add"));
+  }
+
+  #[test]
+  fn unterminated_slash_falls_back_to_a_literal_substring() {
+    let filter = Filter::parse("/test_add");
+
+    assert!(filter.matches("/test_add_more"));
+    assert!(!filter.matches("test_add"));
+  }
+
+  #[test]
+  fn invalid_regex_falls_back_to_a_literal_substring() {
+    let filter = Filter::parse("/(unclosed/");
+
+    assert!(filter.matches("has a /(unclosed/ in it"));
+    assert!(!filter.matches("test_add"));
+  }
+}
@@ -0,0 +1,228 @@
+//! # Fuzzing
+//!
+//! Coverage-guided generation of inputs for parametrized test functions.
+//!
+//! Feeds the per-test line coverage that [`crate::coverage`] already collects back
+//! into input generation: a small corpus of byte buffers is mutated, decoded into
+//! the function's declared arguments and run. Inputs which hit previously-unseen
+//! lines are kept as new seeds; inputs which raise are minimized and reported.
+
+use crate::{
+  coverage,
+  discovery::{FuzzParameter, FuzzParameterKind, Test},
+  python::{
+    objects::{PyError, PyObject, PyTuple},
+    ActiveInterpreter, Interpreter as _, MainInterpreter, SubInterpreter,
+  },
+  rng::Rng,
+  run::Error,
+};
+use std::time::{Duration, Instant};
+
+/// Result of running a coverage-guided fuzz campaign against a test function
+pub struct FuzzOutcome {
+  pub time: Duration,
+  pub iterations: u32,
+  pub failure: Option<(Error, Vec<u8>)>,
+}
+
+/// Runs the fuzz loop for a single parametrized test function.
+///
+/// Each iteration picks a corpus entry, mutates it, decodes it into the function's
+/// arguments, and runs the test in a fresh [`SubInterpreter`] so state from prior
+/// iterations cannot leak between runs.
+pub fn run(interpreter: &MainInterpreter, test: &Test, max_iterations: u32) -> FuzzOutcome {
+  let start_time = Instant::now();
+  let parameters = test.parameters();
+
+  let mut corpus: Vec<Vec<u8>> = vec![vec![0; parameters.len().max(1) * 4]];
+  let mut global_coverage = coverage::Lines::default();
+  let mut rng = Rng::new(0x2545_f491_4f6c_dd1d);
+
+  for iteration in 0..max_iterations {
+    let input = mutate(&mut rng, &corpus);
+
+    let mut subinterpreter = SubInterpreter::new(interpreter);
+    subinterpreter.enable_coverage();
+
+    let result = subinterpreter.with_gil(|python| {
+      python.capture_output();
+      python.add_parent_module_to_path(test.file());
+      run_input(python, test, &input)
+    });
+    let coverage = subinterpreter.get_coverage().unwrap_or_default();
+
+    if let Err(error) = result {
+      // A test that skips itself (e.g. `unittest.SkipTest`/`pytest.skip`) isn't a failure -
+      // the fuzz loop should just move on to the next input rather than reporting a crash
+      if !error.is_skip_exception() {
+        let minimized = minimize(interpreter, test, &input, &error.kind);
+        return FuzzOutcome {
+          time: start_time.elapsed(),
+          iterations: iteration + 1,
+          failure: Some((error, minimized)),
+        };
+      }
+    }
+
+    if has_new_lines(&global_coverage, &coverage) {
+      global_coverage.merge(coverage);
+      corpus.push(input);
+    }
+  }
+
+  FuzzOutcome {
+    time: start_time.elapsed(),
+    iterations: max_iterations,
+    failure: None,
+  }
+}
+
+/// Runs the test's module and calls the function with arguments decoded from `input`
+fn run_input(python: &ActiveInterpreter, test: &Test, input: &[u8]) -> Result<(), Error> {
+  let run = || -> Result<(), PyError> {
+    let module = python.execute_file(test.file())?;
+    let function = module.get_attr(python, &python.new_string(test.name()))?;
+
+    let args = decode_arguments(python, input, test.parameters());
+    function.call_with_args(&args)?;
+
+    Ok(())
+  };
+
+  run().map_err(|error| Error::from_py_error(python, error))
+}
+
+/// Splits `input` into one chunk per parameter and decodes each chunk into the type its
+/// declared annotation names, falling back to an `int` for unannotated/unrecognised ones
+fn decode_arguments(
+  python: &ActiveInterpreter,
+  input: &[u8],
+  parameters: &[FuzzParameter],
+) -> PyTuple {
+  let chunk_size = (input.len() / parameters.len().max(1)).max(1);
+
+  let items: Vec<PyObject> = parameters
+    .iter()
+    .enumerate()
+    .map(|(index, parameter)| {
+      let start = index * chunk_size;
+      let chunk = input.get(start..start + chunk_size).unwrap_or(&[]);
+      decode_argument(python, chunk, parameter.kind)
+    })
+    .collect();
+
+  PyTuple::new(items)
+}
+
+/// Decodes a single mutated chunk into `kind`'s Python representation
+fn decode_argument(python: &ActiveInterpreter, chunk: &[u8], kind: FuzzParameterKind) -> PyObject {
+  match kind {
+    FuzzParameterKind::Int => python.new_int(decode_i64(chunk)),
+    FuzzParameterKind::Float => python.new_float(decode_f64(chunk)),
+    FuzzParameterKind::Bool => python.new_bool(chunk.first().is_some_and(|byte| *byte & 1 == 1)),
+    FuzzParameterKind::Str => python.new_string(&String::from_utf8_lossy(chunk)),
+    FuzzParameterKind::Bytes => python.new_bytes(chunk),
+  }
+}
+
+fn decode_i64(chunk: &[u8]) -> i64 {
+  let mut bytes = [0u8; 8];
+  for (destination, source) in bytes.iter_mut().zip(chunk) {
+    *destination = *source;
+  }
+  i64::from_le_bytes(bytes)
+}
+
+fn decode_f64(chunk: &[u8]) -> f64 {
+  let mut bytes = [0u8; 8];
+  for (destination, source) in bytes.iter_mut().zip(chunk) {
+    *destination = *source;
+  }
+  f64::from_le_bytes(bytes)
+}
+
+/// Repeatedly shrinks `input`, keeping any smaller buffer which still reproduces
+/// an exception of the same `kind`
+fn minimize(interpreter: &MainInterpreter, test: &Test, input: &[u8], kind: &str) -> Vec<u8> {
+  let mut minimized = input.to_vec();
+
+  let mut shrunk = true;
+  while shrunk {
+    shrunk = false;
+
+    for chunk_len in [minimized.len() / 2, minimized.len() / 4, 1] {
+      if chunk_len == 0 || chunk_len >= minimized.len() {
+        continue;
+      }
+
+      let mut candidate = minimized.clone();
+      candidate.drain(0..chunk_len);
+
+      let mut subinterpreter = SubInterpreter::new(interpreter);
+      let still_fails = subinterpreter.with_gil(|python| {
+        python.capture_output();
+        python.add_parent_module_to_path(test.file());
+
+        matches!(run_input(python, test, &candidate), Err(error) if error.kind == kind)
+      });
+
+      if still_fails {
+        minimized = candidate;
+        shrunk = true;
+        break;
+      }
+    }
+  }
+
+  minimized
+}
+
+fn has_new_lines(seen: &coverage::Lines, candidate: &coverage::Lines) -> bool {
+  candidate
+    .iter()
+    .any(|(file, lines)| match seen.get_lines(file) {
+      Some(seen_lines) => !lines.is_subset(seen_lines),
+      None => true,
+    })
+}
+
+/// Applies a random byte-level mutation to a corpus entry: a bit flip, a byte
+/// increment, a block insert/delete, or a splice with another corpus entry
+fn mutate(rng: &mut Rng, corpus: &[Vec<u8>]) -> Vec<u8> {
+  let mut buffer = corpus[rng.below(corpus.len())].clone();
+  if buffer.is_empty() {
+    buffer.push(0);
+  }
+
+  match rng.below(5) {
+    0 => {
+      let index = rng.below(buffer.len());
+      buffer[index] ^= 1 << rng.below(8);
+    }
+    1 => {
+      let index = rng.below(buffer.len());
+      buffer[index] = buffer[index].wrapping_add(1);
+    }
+    2 => {
+      let index = rng.below(buffer.len() + 1);
+      let block: Vec<u8> = (0..=rng.below(8)).map(|_| rng.below(256) as u8).collect();
+      buffer.splice(index..index, block);
+    }
+    3 if buffer.len() > 1 => {
+      let start = rng.below(buffer.len());
+      let len = 1 + rng.below((buffer.len() - start).min(buffer.len() - 1));
+      buffer.drain(start..start + len);
+    }
+    _ => {
+      let other = &corpus[rng.below(corpus.len())];
+      if !other.is_empty() {
+        let split = rng.below(other.len());
+        buffer.truncate(rng.below(buffer.len() + 1));
+        buffer.extend_from_slice(&other[split..]);
+      }
+    }
+  }
+
+  buffer
+}
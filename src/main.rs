@@ -4,11 +4,18 @@
 mod config;
 mod coverage;
 mod discovery;
+mod expectations;
+mod filter;
+mod fuzz;
 mod output;
 mod python;
+mod rng;
 mod run;
+mod watch;
 
-use python::Interpreter;
+use config::Settings;
+use output::Reporter;
+use python::{Interpreter, MainInterpreter};
 use rayon::prelude::*;
 use run::TestOutcome;
 use std::{
@@ -19,56 +26,27 @@ use std::{
 fn main() -> ExitCode {
   let settings = config::read_settings();
 
+  if settings.watch {
+    watch::run(&settings);
+    return ExitCode::SUCCESS;
+  }
+
   let mut reporter = output::new_reporter(settings.output);
   reporter.initialize(python::version());
 
-  // Discover tests
-  let discovered = discovery::find_tests(&settings.paths, &settings.exclude);
-  reporter.discovered(&discovered);
-
   // Main Python interpreter must be initialized in the main thread
-  let mut interpreter = python::MainInterpreter::initialize();
+  let mut interpreter = MainInterpreter::initialize(settings.embedded_stdlib);
   interpreter.with_gil(|python| {
     // The decimal module crashes Python 3.12 if it is initialised multiple times
     // If not initialised in the base interpreter, if a subinterpreter imports it it will crash
     _ = python.import_module(c"decimal");
   });
 
-  // Run tests
-  let results: TestSummary = discovered
-    .tests
-    .par_iter()
-    .map(|test| {
-      let mut subinterpreter = python::SubInterpreter::new(&interpreter);
-
-      if settings.coverage.enabled {
-        subinterpreter.enable_coverage();
-      }
-
-      let outcome = subinterpreter.with_gil(|python| {
-        python.capture_output();
-        python.add_parent_module_to_path(test.file());
-
-        run::test(python, test)
-      });
-      let coverage = subinterpreter.get_coverage();
-
-      (outcome, coverage)
-    })
-    .inspect(|(outcome, _coverage)| {
-      reporter.result(outcome);
-
-      if !settings.no_fail_fast && outcome.is_fail() {
-        reporter.fail_fast_error(outcome);
-        process::exit(1);
-      }
-    })
-    .collect();
-
-  // Report results
-  reporter.summary(&results);
+  let discovered = discover_tests(&settings, &interpreter, reporter.as_mut());
+  let results = run_tests(&settings, &interpreter, &discovered, reporter.as_mut());
 
   let successful = results.failed == 0 && results.passed > 0;
+  let mut coverage_gate_passed = true;
 
   if settings.coverage.enabled && successful {
     let coverage_include = if settings.coverage.include.is_empty() {
@@ -84,16 +62,171 @@ fn main() -> ExitCode {
 
     let possible_lines =
       coverage::get_executable_lines(&interpreter, coverage_include, coverage_exclude);
-    coverage::print_summary(&possible_lines, &results.executed_lines);
+    coverage::print_summary(
+      &possible_lines,
+      &results.executed_lines,
+      settings.coverage.skip_covered,
+    );
+
+    if let Some(lcov_path) = &settings.coverage.lcov {
+      coverage::write_lcov(lcov_path, &possible_lines, &results.executed_lines)
+        .expect("coverage LCOV file to be writable");
+    }
+    if let Some(cobertura_path) = &settings.coverage.cobertura {
+      coverage::write_cobertura(cobertura_path, &possible_lines, &results.executed_lines)
+        .expect("coverage Cobertura file to be writable");
+    }
+
+    if let Some(threshold) = settings.coverage.fail_under {
+      coverage_gate_passed =
+        coverage::check_fail_under(&possible_lines, &results.executed_lines, threshold);
+    }
   }
 
-  if successful {
+  if successful && coverage_gate_passed {
     ExitCode::SUCCESS
   } else {
     ExitCode::FAILURE
   }
 }
 
+/// Generates a seed to shuffle the test order with, when `--shuffle` is passed without one
+fn generate_seed() -> u64 {
+  use std::time::{SystemTime, UNIX_EPOCH};
+
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .unwrap_or_default()
+    .as_nanos();
+
+  #[expect(clippy::cast_possible_truncation, reason = "any seed value is fine")]
+  let seed = nanos as u64;
+  seed
+}
+
+/// Discovers every test matching `settings` (applying `--shuffle`/`--filter`) and reports them
+///
+/// Shared between a normal run and each iteration of [`watch::run`], which both need the
+/// discovered tests to outlive the [`run_tests`] call that borrows from them
+pub(crate) fn discover_tests(
+  settings: &Settings,
+  interpreter: &MainInterpreter,
+  reporter: &mut dyn Reporter,
+) -> discovery::DiscoveredTests {
+  let mut discovered = discovery::find_tests(&settings.paths, &settings.exclude);
+  if settings.doctest {
+    let doctests = discovery::find_doctests(interpreter, &settings.paths, &settings.exclude);
+    discovered.test_count += doctests.len();
+    discovered.tests.extend(doctests);
+  }
+  if settings.shuffle {
+    let seed = settings.shuffle_seed.unwrap_or_else(generate_seed);
+    rng::Rng::new(seed).shuffle(&mut discovered.tests);
+    discovered.shuffle_seed = Some(seed);
+  }
+  if let Some(pattern) = &settings.filter {
+    let found = discovered.tests.len();
+    filter::apply(&mut discovered.tests, &filter::Filter::parse(pattern));
+    discovered.test_count = discovered.tests.len();
+    discovered.filtered_count = Some(found);
+  }
+  reporter.discovered(&discovered);
+
+  discovered
+}
+
+/// Runs every test in `discovered`, reporting results as they complete
+///
+/// Shared between a normal run and each iteration of [`watch::run`]. Takes `discovered` by
+/// reference, rather than discovering tests itself, so that the [`TestOutcome`]s this returns
+/// (which borrow their [`discovery::Test`] for the lifetime `'tests`) can borrow from a value
+/// that outlives this call, instead of one owned by this function itself
+pub(crate) fn run_tests<'tests>(
+  settings: &Settings,
+  interpreter: &MainInterpreter,
+  discovered: &'tests discovery::DiscoveredTests,
+  reporter: &mut dyn Reporter,
+) -> TestSummary<'tests> {
+  let expectations = settings
+    .expectations
+    .as_ref()
+    .map(|path| expectations::Expectations::read(path))
+    .unwrap_or_default();
+
+  let units = run::schedule(&discovered.tests, settings.fuzz.enabled);
+
+  let results: TestSummary = units
+    .par_iter()
+    .flat_map(|unit| {
+      let tests = match unit {
+        run::Unit::Fuzz(test) => {
+          let test = *test;
+          let fuzz::FuzzOutcome {
+            time,
+            iterations,
+            failure,
+          } = fuzz::run(interpreter, test, settings.fuzz.iterations);
+
+          let outcome = run::TestOutcome::fuzzed(test, time, iterations, failure);
+          return vec![(outcome, None)];
+        }
+        run::Unit::Group(tests) => tests,
+      };
+
+      let mut subinterpreter = python::SubInterpreter::new(interpreter);
+
+      if settings.coverage.enabled {
+        subinterpreter.enable_coverage();
+      }
+      if settings.memory_profile {
+        subinterpreter.enable_memory_tracking();
+      }
+
+      let interrupt_handle = subinterpreter.interrupt_handle();
+      let outcomes = subinterpreter.with_gil(|python| {
+        python.capture_output();
+        python.add_parent_module_to_path(tests[0].file());
+
+        match settings.timeout {
+          Some(timeout) => run::test_group_with_timeout(
+            python,
+            tests,
+            interrupt_handle,
+            Duration::from_millis(timeout),
+          ),
+          None => run::test_group(python, tests),
+        }
+      });
+      let outcomes = run::retry_failures(interpreter, settings.retries, outcomes);
+
+      // Every test in the group ran in the same subinterpreter, so its coverage is
+      // identical - only attach it to the first case to avoid merging it redundantly
+      let mut coverage = subinterpreter.get_coverage();
+
+      outcomes
+        .into_iter()
+        .map(|outcome| {
+          let outcome = outcome.reconcile_expectation(&expectations);
+
+          (outcome, coverage.take())
+        })
+        .collect()
+    })
+    .inspect(|(outcome, _coverage)| {
+      reporter.result(outcome);
+
+      if !settings.no_fail_fast && outcome.is_fail() {
+        reporter.fail_fast_error(outcome);
+        process::exit(1);
+      }
+    })
+    .collect();
+
+  reporter.summary(&results);
+
+  results
+}
+
 /// Summary of all tests that were run
 #[derive(Clone, Debug, Default)]
 pub struct TestSummary<'tests> {
@@ -102,6 +235,8 @@ pub struct TestSummary<'tests> {
   pub passed: usize,
   pub skipped: usize,
   pub failed: usize,
+  /// Tests that failed at least once, but passed after being re-run under `--retries`
+  pub flaky: usize,
 
   pub tests: Vec<TestOutcome<'tests>>,
   pub executed_lines: coverage::Lines,
@@ -124,11 +259,16 @@ impl<'tests> FromParallelIterator<(TestOutcome<'tests>, Option<coverage::Lines>)
     let (tests, executed_lines): (Vec<_>, coverage::Lines) = iter.into_par_iter().unzip();
     let duration = start_time.elapsed();
 
-    let (mut passed, mut skipped, mut failed) = (0, 0, 0);
+    let (mut passed, mut skipped, mut failed, mut flaky) = (0, 0, 0, 0);
     for test in &tests {
       match test.outcome {
-        run::OutcomeKind::Pass { .. } => passed += 1,
+        run::OutcomeKind::Pass { .. } | run::OutcomeKind::Busted { .. } => passed += 1,
         run::OutcomeKind::Skip { .. } => skipped += 1,
+        run::OutcomeKind::Fuzzed { ref failure, .. } if failure.is_none() => passed += 1,
+        run::OutcomeKind::Flaky { .. } => {
+          passed += 1;
+          flaky += 1;
+        }
         _ => failed += 1,
       };
     }
@@ -138,6 +278,7 @@ impl<'tests> FromParallelIterator<(TestOutcome<'tests>, Option<coverage::Lines>)
       passed,
       skipped,
       failed,
+      flaky,
       tests,
       executed_lines,
     }
@@ -0,0 +1,122 @@
+use super::{standard::ProgressReporter, Reporter};
+use crate::{
+  discovery::DiscoveredTests,
+  run::{Error, TestOutcome},
+  TestSummary,
+};
+
+use std::env;
+
+/// Wraps [`ProgressReporter`], additionally emitting GitHub Actions workflow commands so
+/// failures show up as inline annotations on the PR diff - auto-selected when `$GITHUB_ACTIONS`
+/// is set and the user hasn't asked for a different format themselves
+///
+/// Every test's normal output is wrapped in a `::group::`/`::endgroup::` block, which GitHub
+/// folds in the log by default, so the verbose native output doesn't dominate the job log.
+/// Every failure additionally gets an `::error ...::` command pointing at the traceback frame
+/// closest to where it was raised, alongside (not instead of) the normal progress/summary
+/// output - a CI log with only annotations and no human-readable output would be unreadable
+/// outside GitHub's PR diff view
+pub struct GitHubReporter {
+  inner: ProgressReporter,
+}
+impl GitHubReporter {
+  pub fn new() -> Self {
+    Self {
+      inner: ProgressReporter::new(),
+    }
+  }
+}
+impl Reporter for GitHubReporter {
+  fn initialize(&mut self, python_version: String) {
+    self.inner.initialize(python_version);
+  }
+
+  fn discovered(&mut self, discovered: &DiscoveredTests) {
+    self.inner.discovered(discovered);
+  }
+
+  fn result(&self, result: &TestOutcome) {
+    // `ProgressReporter::result` writes to stderr (it has to, to coexist with its progress
+    // bar), so the group markers are written there too - splitting them across stdout and
+    // stderr would risk the two streams interleaving out of order in the combined job log
+    eprintln!("::group::{}", result.identifier());
+    self.inner.result(result);
+
+    if let Some(error) = result.error() {
+      print_annotation(&result.identifier(), error);
+    }
+    eprintln!("::endgroup::");
+  }
+
+  fn fail_fast_error(&self, result: &TestOutcome) {
+    self.inner.fail_fast_error(result);
+  }
+
+  fn summary(&mut self, summary: &TestSummary) {
+    self.inner.summary(summary);
+  }
+}
+
+/// Prints an `::error file=...,line=...,title=...::kind: message` command for the deepest
+/// frame of `error`'s traceback that lies within the project - the frame closest to where the
+/// exception was raised that GitHub can actually annotate. A failure raised from inside the
+/// standard library or an installed dependency would otherwise point at a file GitHub has no
+/// diff for, and the annotation just wouldn't render, so frames outside the current directory
+/// are skipped in favour of the deepest one that's in it. Falls back to the deepest frame of
+/// all if none of them are
+fn print_annotation(title: &str, error: &Error) {
+  let project_root = env::current_dir().unwrap_or_default();
+
+  let frame = error.traceback.as_ref().and_then(|traceback| {
+    traceback
+      .frames
+      .iter()
+      .rev()
+      .find(|frame| frame.file.starts_with(&project_root))
+      .or_else(|| traceback.frames.last())
+  });
+
+  let file = frame.map_or_else(String::new, |frame| frame.file.display().to_string());
+  let line = frame.map_or(0, |frame| frame.line);
+
+  println!(
+    "::error file={},line={line},title={}::{}: {}",
+    escape_property(&file),
+    escape_property(title),
+    escape_data(&error.kind),
+    escape_data(&error.message),
+  );
+}
+
+/// Escapes the characters GitHub workflow commands treat as special in a command's data
+/// (the part after the final `::`), since the command itself must stay on a single line
+fn escape_data(value: &str) -> String {
+  value
+    .replace('%', "%25")
+    .replace('\r', "%0D")
+    .replace('\n', "%0A")
+}
+
+/// Escapes the characters GitHub workflow commands treat as special in a `key=value` property
+fn escape_property(value: &str) -> String {
+  escape_data(value).replace(':', "%3A").replace(',', "%2C")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{escape_data, escape_property};
+
+  #[test]
+  fn escape_data_escapes_percent_and_newlines() {
+    assert_eq!(escape_data("100% done\r\nnext line"), "100%25 done%0D%0Anext line");
+  }
+
+  #[test]
+  fn escape_property_additionally_escapes_colons_and_commas() {
+    assert_eq!(
+      escape_property("C:\\path, with a comma"),
+      "C%3A\\path%2C with a comma"
+    );
+  }
+}
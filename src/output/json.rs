@@ -1,7 +1,8 @@
 use super::Reporter;
 use crate::{
-  python,
-  run::{OutcomeKind, TestOutcome},
+  discovery::DiscoveredTests,
+  run::{self, OutcomeKind, TestOutcome},
+  TestSummary,
 };
 
 use serde::{Deserialize, Serialize};
@@ -11,18 +12,74 @@ use std::{
   time::Duration,
 };
 
+/// Emits a stream of newline-delimited JSON events: one `discovered` event, one `result`
+/// event per test, then a final `summary` event. Each event is written and flushed as soon
+/// as it is available, so CI systems and editors can ingest failures as they happen rather
+/// than waiting for the whole run to finish.
 pub struct JSONReporter;
 impl Reporter for JSONReporter {
+  fn discovered(&mut self, discovered: &DiscoveredTests) {
+    write_event(&Event::Discovered {
+      file_count: discovered.file_count,
+      test_count: discovered.test_count,
+      duration: discovered.duration,
+      shuffle_seed: discovered.shuffle_seed,
+      filtered_count: discovered.filtered_count,
+    });
+  }
+
   fn result(&self, result: &TestOutcome) {
-    let mut stdout = io::BufWriter::new(io::stdout());
-    let result = JSONTestOutput::from(result);
+    write_event(&Event::Result(JSONTestOutput::from(result)));
+  }
 
-    serde_json::to_writer(&mut stdout, &result).unwrap();
-    writeln!(&mut stdout).unwrap();
-    stdout.flush().unwrap();
+  fn summary(&mut self, summary: &TestSummary) {
+    write_event(&Event::Summary {
+      passed: summary.passed,
+      failed: summary.failed,
+      skipped: summary.skipped,
+      flaky: summary.flaky,
+      duration: summary.duration,
+    });
   }
 }
 
+fn write_event(event: &Event) {
+  let mut stdout = io::BufWriter::new(io::stdout());
+
+  serde_json::to_writer(&mut stdout, event).unwrap();
+  writeln!(&mut stdout).unwrap();
+  stdout.flush().unwrap();
+}
+
+/// A single entry in the newline-delimited JSON result stream
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+  /// Emitted once, after test discovery has finished
+  Discovered {
+    file_count: usize,
+    test_count: usize,
+    duration: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shuffle_seed: Option<u64>,
+    /// How many tests were found before `--filter` narrowed them down, if one was passed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filtered_count: Option<usize>,
+  },
+  /// Emitted once per test, as soon as it finishes running
+  Result(JSONTestOutput),
+  /// Emitted once, after every test has been run
+  Summary {
+    passed: usize,
+    failed: usize,
+    skipped: usize,
+    /// How many of `passed` failed at least once, but passed after being re-run under
+    /// `--retries`
+    flaky: usize,
+    duration: Duration,
+  },
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct JSONTestOutput {
   pub file: PathBuf,
@@ -30,7 +87,7 @@ pub struct JSONTestOutput {
   pub outcome: Outcome,
 
   #[serde(skip_serializing_if = "Option::is_none")]
-  pub error: Option<python::Error>,
+  pub error: Option<run::Error>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub time: Option<Duration>,
 }
@@ -42,6 +99,12 @@ pub enum Outcome {
   Skip,
   ExpectedFailure,
   NonTestFail,
+  /// Listed in the expectations manifest, and failed/errored as required
+  Busted,
+  /// Listed in the expectations manifest, but passed when a failure/error was required
+  UnexpectedPass,
+  /// Failed at least once, but passed after being re-run under `--retries`
+  Flaky,
 }
 
 impl From<&TestOutcome<'_>> for JSONTestOutput {
@@ -62,7 +125,19 @@ impl From<&OutcomeKind> for Outcome {
       OutcomeKind::Skip { .. } => Self::Skip,
       OutcomeKind::Fail { .. } | OutcomeKind::Error { .. } => Self::Fail,
       OutcomeKind::ExpectedFailure { .. } => Self::ExpectedFailure,
-      OutcomeKind::ModuleError { .. } | OutcomeKind::TestNotFound => Self::NonTestFail,
+      OutcomeKind::Busted { .. } => Self::Busted,
+      OutcomeKind::UnexpectedPass { .. } => Self::UnexpectedPass,
+      OutcomeKind::Flaky { .. } => Self::Flaky,
+      OutcomeKind::ModuleError { .. } | OutcomeKind::TestNotFound | OutcomeKind::Timeout => {
+        Self::NonTestFail
+      }
+      OutcomeKind::Fuzzed { failure, .. } => {
+        if failure.is_some() {
+          Self::Fail
+        } else {
+          Self::Pass
+        }
+      }
     }
   }
 }
@@ -0,0 +1,189 @@
+use super::{
+  json::{JSONTestOutput, Outcome},
+  Reporter,
+};
+use crate::{
+  run::{self, TestOutcome},
+  TestSummary,
+};
+
+use std::{collections::BTreeMap, fmt::Write as _, path::Path, sync::Mutex};
+
+/// Emits every test's result as a single JUnit XML document, for ingestion by CI dashboards
+/// that expect it (e.g. the same reporters Deno targets)
+///
+/// JUnit requires per-`<testsuite>` `tests`/`failures`/`skipped`/`time` totals, so results are
+/// buffered until the run finishes rather than streamed like [`super::json::JSONReporter`].
+/// Results are grouped into one `<testsuite>` per source file
+pub struct JUnitReporter {
+  results: Mutex<Vec<JSONTestOutput>>,
+}
+impl JUnitReporter {
+  pub fn new() -> Self {
+    Self {
+      results: Mutex::new(Vec::new()),
+    }
+  }
+}
+impl Reporter for JUnitReporter {
+  fn result(&self, result: &TestOutcome) {
+    self
+      .results
+      .lock()
+      .expect("lock isn't poisoned")
+      .push(JSONTestOutput::from(result));
+  }
+
+  // `run_tests` exits the process right after this, before `summary` would ever run - so the
+  // buffered document has to be flushed here too, or a fail-fast run emits nothing at all
+  fn fail_fast_error(&self, _result: &TestOutcome) {
+    let results = self.results.lock().expect("lock isn't poisoned");
+    print_document(&results);
+  }
+
+  fn summary(&mut self, _summary: &TestSummary) {
+    let results = self.results.get_mut().expect("lock isn't poisoned");
+    print_document(results);
+  }
+}
+
+fn print_document(results: &[JSONTestOutput]) {
+  let mut suites: BTreeMap<&Path, Vec<&JSONTestOutput>> = BTreeMap::new();
+  for result in results {
+    suites.entry(&result.file).or_default().push(result);
+  }
+
+  let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+  for (file, tests) in suites {
+    write_testsuite(&mut xml, file, &tests);
+  }
+  xml.push_str("</testsuites>\n");
+
+  print!("{xml}");
+}
+
+fn write_testsuite(xml: &mut String, file: &Path, tests: &[&JSONTestOutput]) {
+  let failures = tests
+    .iter()
+    .filter(|test| {
+      matches!(
+        test.outcome,
+        Outcome::Fail | Outcome::NonTestFail | Outcome::ExpectedFailure | Outcome::UnexpectedPass
+      )
+    })
+    .count();
+  let skipped = tests
+    .iter()
+    .filter(|test| test.outcome == Outcome::Skip)
+    .count();
+  let time: f64 = tests
+    .iter()
+    .filter_map(|test| test.time)
+    .map(|time| time.as_secs_f64())
+    .sum();
+
+  let _ = writeln!(
+    xml,
+    "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{failures}\" skipped=\"{skipped}\" time=\"{time:.3}\">",
+    escape(&file.display().to_string()),
+    tests.len(),
+  );
+
+  for test in tests {
+    write_testcase(xml, file, test);
+  }
+
+  xml.push_str("  </testsuite>\n");
+}
+
+fn write_testcase(xml: &mut String, file: &Path, test: &JSONTestOutput) {
+  let time = test.time.unwrap_or_default().as_secs_f64();
+
+  let _ = write!(
+    xml,
+    "    <testcase classname=\"{}\" name=\"{}\" time=\"{time:.3}\"",
+    escape(&file.display().to_string()),
+    escape(&test.test_identifier),
+  );
+
+  // JUnit consumers (e.g. CI dashboards) only render a test as skipped when it has a
+  // `<skipped/>` child - a bare `<testcase/>` with no error reads as passed
+  if test.outcome == Outcome::Skip {
+    xml.push_str(">\n      <skipped />\n    </testcase>\n");
+    return;
+  }
+
+  let Some(error) = &test.error else {
+    xml.push_str(" />\n");
+    return;
+  };
+
+  xml.push_str(">\n");
+
+  let _ = writeln!(
+    xml,
+    "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+    escape(&error.message),
+    escape(&error.kind),
+    escape(&traceback_text(error)),
+  );
+
+  if let Some(stdout) = &error.stdout {
+    let _ = writeln!(xml, "      <system-out>{}</system-out>", escape(stdout));
+  }
+  if let Some(stderr) = &error.stderr {
+    let _ = writeln!(xml, "      <system-err>{}</system-err>", escape(stderr));
+  }
+
+  xml.push_str("    </testcase>\n");
+}
+
+/// Renders a traceback as a multi-line string, one frame per line
+fn traceback_text(error: &run::Error) -> String {
+  let Some(traceback) = &error.traceback else {
+    return String::new();
+  };
+
+  traceback
+    .frames
+    .iter()
+    .map(|frame| {
+      format!(
+        "{} ({}:{})",
+        frame.function,
+        frame.file.display(),
+        frame.line
+      )
+    })
+    .collect::<Vec<_>>()
+    .join("\n")
+}
+
+/// Escapes the characters XML treats as special, so arbitrary test output can be embedded
+/// as element text or an attribute value
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+    .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::escape;
+
+  #[test]
+  fn escapes_all_xml_special_characters() {
+    assert_eq!(
+      escape(r#"<tag a="b" c='d'> & text"#),
+      "&lt;tag a=&quot;b&quot; c=&apos;d&apos;&gt; &amp; text"
+    );
+  }
+
+  #[test]
+  fn leaves_plain_text_unchanged() {
+    assert_eq!(escape("test_add passed"), "test_add passed");
+  }
+}
@@ -8,15 +8,23 @@ pub trait Reporter: Sync {
   fn summary(&mut self, _summary: &TestSummary) {}
 }
 
+mod github;
+use github::GitHubReporter;
+
 pub(crate) mod json;
 use json::JSONReporter;
 
-mod standard;
+mod junit;
+use junit::JUnitReporter;
+
+pub(crate) mod standard;
 use standard::ProgressReporter;
 
 pub fn new_reporter(format: OutputFormat) -> Box<dyn Reporter> {
   match format {
     OutputFormat::Standard => Box::new(ProgressReporter::new()),
     OutputFormat::Json => Box::new(JSONReporter),
+    OutputFormat::Junit => Box::new(JUnitReporter::new()),
+    OutputFormat::GitHub => Box::new(GitHubReporter::new()),
   }
 }
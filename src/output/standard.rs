@@ -1,7 +1,7 @@
 use super::Reporter;
 use crate::{
   discovery::DiscoveredTests,
-  run::{OutcomeKind, TestOutcome},
+  run::{self, OutcomeKind, TestOutcome},
   TestSummary,
 };
 
@@ -42,12 +42,20 @@ impl Reporter for ProgressReporter {
   }
 
   fn discovered(&mut self, discovered: &DiscoveredTests) {
-    eprintln!(
+    eprint!(
       "   Found {} tests from {} files in {:.2}s",
-      discovered.tests.len().bold(),
+      discovered.filtered_count.unwrap_or(discovered.tests.len()).bold(),
       discovered.file_count.bold(),
       discovered.duration.as_secs_f64()
     );
+    if discovered.filtered_count.is_some() {
+      eprint!(", {} selected", discovered.tests.len().bold());
+    }
+    eprintln!();
+
+    if let Some(seed) = discovered.shuffle_seed {
+      eprintln!("   Shuffling tests with seed {}", seed.bold());
+    }
 
     self.create_progress_bar(discovered.test_count);
   }
@@ -77,6 +85,8 @@ impl Reporter for ProgressReporter {
         error(result).unwrap();
       }
     }
+
+    heaviest_tests(summary);
   }
 }
 
@@ -85,6 +95,8 @@ fn test_result(w: &mut dyn io::Write, test: &TestOutcome) -> io::Result<()> {
     OutcomeKind::Skip { .. } => write!(w, "{:>10} ", "SKIP".bold().yellow())?,
     OutcomeKind::Pass { .. } => write!(w, "{:>10} ", "PASS".bold().green())?,
     OutcomeKind::Fail { .. } => write!(w, "{:>10} ", "FAIL".bold().red())?,
+    OutcomeKind::Busted { .. } => write!(w, "{:>10} ", "BUSTED".bold().yellow())?,
+    OutcomeKind::Flaky { .. } => write!(w, "{:>10} ", "FLAKY".bold().yellow())?,
     _ => write!(w, "{:>10} ", "ERROR".bold().red())?,
   }
 
@@ -100,10 +112,60 @@ fn test_result(w: &mut dyn io::Write, test: &TestOutcome) -> io::Result<()> {
     write!(w, "{}.", suite.blue())?;
   }
   write!(w, "{}", test.name().bold().blue())?;
+  if let Some(case) = test.case() {
+    write!(w, "{}", format!("[{case}]").dimmed())?;
+  }
+
+  if let OutcomeKind::Flaky { attempts, .. } = test.outcome {
+    write!(w, " {}", format!("[passed after {attempts} retries]").dimmed())?;
+  }
+
+  if let Some(peak_memory) = test.peak_memory() {
+    write!(w, " {}", format!("[peak {}]", format_bytes(peak_memory)).dimmed())?;
+  }
 
   writeln!(w)
 }
 
+/// Prints the tests with the highest peak memory usage, if memory tracking was enabled
+fn heaviest_tests(summary: &TestSummary) {
+  let mut by_memory: Vec<_> = summary
+    .tests
+    .iter()
+    .filter_map(|test| Some((test, test.peak_memory()?)))
+    .collect();
+
+  if by_memory.is_empty() {
+    return;
+  }
+
+  by_memory.sort_by_key(|(_, peak_memory)| std::cmp::Reverse(*peak_memory));
+
+  eprintln!("\n{}{}", "╭─ ".dimmed(), "Heaviest tests".bold());
+  for (test, peak_memory) in by_memory.iter().take(5) {
+    eprintln!(
+      "{}{:>10}  {}",
+      "├─ ".dimmed(),
+      format_bytes(*peak_memory),
+      test.identifier(),
+    );
+  }
+  eprintln!("{}", "╰──".dimmed());
+}
+
+fn format_bytes(bytes: usize) -> String {
+  #[expect(clippy::cast_precision_loss, reason = "byte counts < f64::MAX")]
+  let bytes = bytes as f64;
+
+  if bytes >= 1024.0 * 1024.0 {
+    format!("{:.2} MiB", bytes / (1024.0 * 1024.0))
+  } else if bytes >= 1024.0 {
+    format!("{:.2} KiB", bytes / 1024.0)
+  } else {
+    format!("{bytes} B")
+  }
+}
+
 fn summary_heading(summary: &TestSummary) {
   let summary_style = match () {
     () if summary.run() == 0 => Style::new().bold().yellow(),
@@ -127,6 +189,11 @@ fn summary_heading(summary: &TestSummary) {
   eprint!(", {} ", summary.skipped.bold());
   eprint!("{}", "skipped".bold().yellow());
 
+  if summary.flaky != 0 {
+    eprint!(", {} ", summary.flaky.bold());
+    eprint!("{}", "flaky".bold().yellow());
+  }
+
   eprintln!();
 }
 
@@ -140,6 +207,9 @@ fn error(test: &TestOutcome) -> io::Result<()> {
     write!(w, "{}.", suite.red())?;
   }
   write!(w, "{}", test.name().red())?;
+  if let Some(case) = test.case() {
+    write!(w, "{}", format!("[{case}]").dimmed())?;
+  }
   writeln!(
     w,
     " {}{}{}",
@@ -152,28 +222,21 @@ fn error(test: &TestOutcome) -> io::Result<()> {
     let message = "Expected test to fail, but it passed";
     return writeln!(w, "{}: {message}\n", "ExpectedFailure".bold());
   }
+  if let OutcomeKind::UnexpectedPass { .. } = test.outcome {
+    let message = "Listed in the expectations file as busted, but passed; update the manifest";
+    return writeln!(w, "{}: {message}\n", "UnexpectedPass".bold());
+  }
   if let OutcomeKind::TestNotFound = test.outcome {
     let message = "Could not find test. This is likely a problem in xc.";
     return writeln!(w, "{}: {message}\n", "TestNotFound".bold());
   }
+  if let OutcomeKind::Timeout = test.outcome {
+    let message = "Test did not complete within the configured --timeout, and was interrupted";
+    return writeln!(w, "{}: {message}\n", "Timeout".bold());
+  }
 
   let error = test.error().expect("variants without error handled");
-  writeln!(w, "{}: {}\n", error.kind.bold(), error.message)?;
-
-  if let Some(traceback) = &error.traceback {
-    frame(
-      &mut w,
-      "Traceback",
-      traceback.frames.iter().map(|frame| {
-        format!(
-          "{} ({}:{})",
-          frame.function,
-          frame.file.display().dimmed(),
-          frame.line.dimmed(),
-        )
-      }),
-    )?;
-  }
+  error_chain(&mut w, error)?;
 
   if let Some(stdout) = &error.stdout {
     if !stdout.is_empty() {
@@ -189,6 +252,54 @@ fn error(test: &TestOutcome) -> io::Result<()> {
   w.flush()
 }
 
+/// Prints `error` and, recursively, the chain of exceptions it was raised whilst
+/// handling - oldest cause first, mirroring Python's own traceback rendering
+fn error_chain(w: &mut dyn io::Write, error: &run::Error) -> io::Result<()> {
+  if let Some(cause) = &error.cause {
+    error_chain(w, cause)?;
+
+    let message = if error.explicit_cause {
+      "The above exception was the direct cause of the following exception:"
+    } else {
+      "During handling of the above exception, another exception occurred:"
+    };
+    writeln!(w, "\n{}\n", message.dimmed())?;
+  }
+
+  writeln!(w, "{}: {}\n", error.kind.bold(), error.message)?;
+
+  if let Some(traceback) = &error.traceback {
+    frame(
+      w,
+      "Traceback",
+      traceback.frames.iter().flat_map(traceback_frame_lines),
+    )?;
+  }
+
+  Ok(())
+}
+
+/// Renders a single traceback frame as the lines `frame()` prints it: a `function (file:line)`
+/// header, followed by its source context (if the file could still be read) and the `repr()` of
+/// any local variables captured at the point it raised
+fn traceback_frame_lines(frame: &run::TracebackFrame) -> Vec<String> {
+  let mut lines = vec![format!(
+    "{} ({}:{})",
+    frame.function,
+    frame.file.display().dimmed(),
+    frame.line.dimmed(),
+  )];
+
+  if let Some(source) = &frame.source {
+    lines.extend(source.lines().map(|line| format!("    {}", line.dimmed())));
+  }
+  for (name, value) in &frame.locals {
+    lines.push(format!("    {} {} {value}", name.blue(), "=".dimmed()));
+  }
+
+  lines
+}
+
 fn frame(
   w: &mut dyn io::Write,
   title: &str,
@@ -0,0 +1,82 @@
+//! Embeds a snapshot of the Python standard library inside the `xc` binary
+//!
+//! [`EMBEDDED_MODULES`] is populated at build time by `build.rs` from the
+//! `XC_EMBED_PYTHON_STDLIB` environment variable, as a list of `(module_name, source)`
+//! pairs. [`install`] registers a `sys.meta_path` finder, itself written in Python, that
+//! serves these modules directly out of the binary instead of reading them from disk.
+//! Modules are embedded as source rather than bytecode, so they are compiled by whichever
+//! CPython `xc` is actually linked against, which avoids having to match a bytecode version.
+
+use super::{
+  objects::{PyObject, PyTuple},
+  ActiveInterpreter,
+};
+use pyo3_ffi::{self as ffi};
+
+include!(concat!(env!("OUT_DIR"), "/embedded_stdlib.rs"));
+
+const FINDER_SOURCE: &std::ffi::CStr = c"
+import importlib.util
+
+
+class _XCEmbeddedFinder:
+    \"\"\"A meta-path finder that serves modules embedded in the xc binary
+
+    Implements the find_spec()/exec_module() protocol importlib has required of meta-path
+    finders since the legacy find_module()/load_module() one was removed in Python 3.12
+    \"\"\"
+
+    def __init__(self, modules):
+        self._modules = modules
+        self._packages = {name.rpartition('.')[0] for name in modules if '.' in name}
+
+    def find_spec(self, name, path, target=None):
+        if name not in self._modules:
+            return None
+        return importlib.util.spec_from_loader(name, self, is_package=name in self._packages)
+
+    def exec_module(self, module):
+        source = self._modules[module.__name__]
+        code = compile(source, f'<embedded {module.__name__}>', 'exec')
+        exec(code, module.__dict__)
+
+
+def create(modules):
+    return _XCEmbeddedFinder(modules)
+";
+
+/// Installs the embedded-stdlib meta-path finder, if any modules were bundled at build time
+///
+/// A no-op when `xc` was built without `XC_EMBED_PYTHON_STDLIB` set, so the default path of
+/// resolving the standard library from the system (or `VIRTUAL_ENV`) Python is unchanged
+pub(crate) fn install(python: &ActiveInterpreter) {
+  if EMBEDDED_MODULES.is_empty() {
+    return;
+  }
+
+  let finder_module = python
+    .execute_string(FINDER_SOURCE)
+    .expect("embedded finder source to compile");
+
+  let modules = unsafe { PyObject::from_ptr_unchecked(ffi::PyDict_New()) };
+  for (name, source) in EMBEDDED_MODULES {
+    unsafe {
+      ffi::PyDict_SetItem(
+        modules.as_ptr(),
+        python.new_string(name).as_ptr(),
+        python.new_string(source).as_ptr(),
+      );
+    }
+  }
+
+  let create = finder_module
+    .get_attr(python, &python.new_string("create"))
+    .expect("embedded finder module to expose `create`");
+  let finder = create
+    .call_with_args(&PyTuple::new(vec![modules]))
+    .expect("embedded finder to be constructed");
+
+  let sys = python.import_module(c"sys");
+  let meta_path = sys.get_attr(python, &python.new_string("meta_path")).unwrap();
+  unsafe { ffi::PyList_Insert(meta_path.as_ptr(), 0, finder.as_ptr()) };
+}
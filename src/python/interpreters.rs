@@ -2,7 +2,7 @@ use pyo3_ffi::{self as ffi};
 use std::{env, mem, ptr};
 use widestring::WideCString;
 
-use super::ActiveInterpreter;
+use super::{embedded, memory, ActiveInterpreter};
 
 /// Interface implemented by both [`MainInterpreter`] and [`SubInterpreter`]
 ///
@@ -36,7 +36,12 @@ pub struct MainInterpreter {
   _virtual_enviroment_path: WideCString,
 }
 impl MainInterpreter {
-  pub fn initialize() -> Self {
+  /// Initializes the main interpreter
+  ///
+  /// When `embedded_stdlib` is set, and `xc` was built with `XC_EMBED_PYTHON_STDLIB`,
+  /// registers a `sys.meta_path` finder that serves the standard library bundled in the
+  /// binary, so `xc` can run as a single executable without a system Python installed
+  pub fn initialize(embedded_stdlib: bool) -> Self {
     let mut config: mem::MaybeUninit<ffi::PyConfig> = mem::MaybeUninit::uninit();
     let mut virtual_enviroment_path: WideCString = WideCString::new();
 
@@ -51,6 +56,11 @@ impl MainInterpreter {
       ffi::Py_InitializeFromConfig(ptr::from_mut(config.assume_init_mut()));
     }
 
+    if embedded_stdlib {
+      // SAFETY: the GIL is held following `Py_InitializeFromConfig`
+      embedded::install(unsafe { &ActiveInterpreter::new() });
+    }
+
     let main_thread_state = unsafe { ffi::PyThreadState_Swap(ptr::null_mut()) };
 
     Self {
@@ -109,6 +119,28 @@ impl SubInterpreter {
 
     Self { interpreter_state }
   }
+
+  /// Enables peak memory tracking for tests run on this subinterpreter
+  ///
+  /// Installs the tracking `PyMem` allocator (a one-off, process-wide operation) and marks
+  /// this thread as tracking, so each test run on it can reset the high-water mark before
+  /// its own invocation and read it back afterwards, rather than sharing one reading across
+  /// every test in the subinterpreter
+  pub fn enable_memory_tracking(&mut self) {
+    memory::install();
+    memory::enable();
+  }
+
+  /// Gets a handle which can be used, from another thread, to interrupt whatever test this
+  /// subinterpreter is currently running (e.g. to enforce a `--timeout`)
+  pub fn interrupt_handle(&self) -> InterruptHandle {
+    // SAFETY: `thread_id` and `interp` are both set once when the thread state is created,
+    // so it is safe to read either without holding the GIL
+    InterruptHandle {
+      thread_id: unsafe { (*self.interpreter_state).thread_id },
+      interpreter: unsafe { (*self.interpreter_state).interp },
+    }
+  }
 }
 impl Interpreter for SubInterpreter {
   fn get_interpreter_state(&self) -> *mut ffi::PyThreadState {
@@ -125,3 +157,40 @@ impl Drop for SubInterpreter {
     unsafe { ffi::Py_EndInterpreter(self.interpreter_state) };
   }
 }
+
+/// A handle that can interrupt a [`SubInterpreter`]'s currently-running test from another
+/// thread, used to enforce `--timeout`
+///
+/// Unlike [`SubInterpreter`] itself, this only holds the OS thread id and interpreter backing
+/// it, not a thread state of its own, so it can be handed to a supervising thread
+#[derive(Clone, Copy)]
+pub struct InterruptHandle {
+  thread_id: std::os::raw::c_ulong,
+  interpreter: *mut ffi::PyInterpreterState,
+}
+// SAFETY: the pointer is only ever dereferenced by `interrupt`, which doesn't mutate anything
+// it points to and is safe to call concurrently with the interpreter it targets
+unsafe impl Send for InterruptHandle {}
+impl InterruptHandle {
+  /// Asynchronously raises `SystemExit` in the targeted subinterpreter's thread
+  ///
+  /// This only takes effect the next time the target thread reaches a Python bytecode
+  /// boundary - a test stuck in a native/C call (e.g. a blocking `socket.recv`) cannot be
+  /// interrupted this way
+  pub fn interrupt(self) {
+    // SAFETY: `PyThreadState_SetAsyncExc` resolves its target thread id within whichever
+    // interpreter the *calling* thread currently has active, and expects the GIL held - the
+    // watchdog thread calling this has no Python thread state of its own, so without one it
+    // would target the wrong (or no) interpreter rather than the subinterpreter actually
+    // running the timed-out test. Attach a throwaway thread state for that subinterpreter
+    // just long enough to make the call, then detach and free it again
+    let thread_state = unsafe { ffi::PyThreadState_New(self.interpreter) };
+    unsafe { ffi::PyEval_RestoreThread(thread_state) };
+
+    unsafe { ffi::PyThreadState_SetAsyncExc(self.thread_id, ffi::PyExc_SystemExit) };
+
+    unsafe { ffi::PyThreadState_Clear(thread_state) };
+    unsafe { ffi::PyEval_SaveThread() };
+    unsafe { ffi::PyThreadState_Delete(thread_state) };
+  }
+}
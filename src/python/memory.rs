@@ -0,0 +1,158 @@
+//! Per-test memory profiling via custom `PyMem` allocators
+//!
+//! Wraps the `PYMEM_DOMAIN_RAW` and `PYMEM_DOMAIN_MEM` allocators with thunks which forward to
+//! the original allocator but additionally track the running total and high-water mark of
+//! allocated bytes. Tracking `RAW` alone isn't enough: with `use_main_obmalloc: 0`, each
+//! subinterpreter gets its own `MEM`/`OBJ` pymalloc state, and nearly all Python-level
+//! allocation goes through `MEM`, not `RAW` - so both are wrapped (falling back to whatever
+//! allocator was previously installed for `MEM`, typically pymalloc itself, rather than
+//! replacing it). `OBJ` is left untracked, since CPython routes it through the `MEM` domain's
+//! arena allocator, so bytes allocated there already show up here. Usage is attributed
+//! per-thread with a thread-local accumulator, reset at the start of each test.
+
+use pyo3_ffi::{self as ffi};
+use std::{
+  cell::{Cell, RefCell},
+  collections::HashMap,
+  sync::{Once, OnceLock},
+};
+
+/// The domains tracked, in the order their original allocators are stored in
+/// [`ORIGINAL_ALLOCATORS`] - a tracking allocator's `ctx` is set to its index into this array,
+/// so the same thunks can serve every tracked domain
+const TRACKED_DOMAINS: [ffi::PyMemAllocatorDomain; 2] = [
+  ffi::PyMemAllocatorDomain::PYMEM_DOMAIN_RAW,
+  ffi::PyMemAllocatorDomain::PYMEM_DOMAIN_MEM,
+];
+
+thread_local! {
+  static TRACKER: RefCell<MemoryTracker> = RefCell::new(MemoryTracker::default());
+  /// Whether this thread wants [`peak`] to report a reading, rather than `None` - set once by
+  /// [`enable`] when `--memory-profile` turns tracking on for this thread's subinterpreter
+  static ENABLED: Cell<bool> = const { Cell::new(false) };
+}
+
+static ORIGINAL_ALLOCATORS: OnceLock<[ffi::PyMemAllocatorEx; TRACKED_DOMAINS.len()]> =
+  OnceLock::new();
+static INSTALLED: Once = Once::new();
+
+#[derive(Default)]
+struct MemoryTracker {
+  /// Size of each currently-live allocation, keyed by its address
+  live: HashMap<usize, usize>,
+  current: usize,
+  peak: usize,
+}
+impl MemoryTracker {
+  fn record_alloc(&mut self, ptr: *mut ffi::c_void, size: usize) {
+    if ptr.is_null() {
+      return;
+    }
+
+    self.live.insert(ptr as usize, size);
+    self.current += size;
+    self.peak = self.peak.max(self.current);
+  }
+
+  fn record_free(&mut self, ptr: *mut ffi::c_void) {
+    if let Some(size) = self.live.remove(&(ptr as usize)) {
+      self.current = self.current.saturating_sub(size);
+    }
+  }
+}
+
+/// Installs the tracking allocator over every domain in [`TRACKED_DOMAINS`], if not already
+/// installed
+///
+/// SAFETY: must be called before the interpreter performs any allocations that need to be
+/// tracked, and must not be called concurrently with allocations
+pub(crate) fn install() {
+  INSTALLED.call_once(|| unsafe {
+    let blank = ffi::PyMemAllocatorEx {
+      ctx: std::ptr::null_mut(),
+      malloc: None,
+      calloc: None,
+      realloc: None,
+      free: None,
+    };
+    let mut originals = [blank; TRACKED_DOMAINS.len()];
+
+    for (index, domain) in TRACKED_DOMAINS.into_iter().enumerate() {
+      ffi::PyMem_GetAllocator(domain, &mut originals[index]);
+
+      let mut tracking = ffi::PyMemAllocatorEx {
+        ctx: index as *mut ffi::c_void,
+        malloc: Some(tracking_malloc),
+        calloc: Some(tracking_calloc),
+        realloc: Some(tracking_realloc),
+        free: Some(tracking_free),
+      };
+      ffi::PyMem_SetAllocator(domain, &mut tracking);
+    }
+
+    ORIGINAL_ALLOCATORS.set(originals).unwrap();
+  });
+}
+
+/// Marks the current thread as tracking memory, so [`peak`] reports a reading instead of
+/// `None` - called once per subinterpreter that has `--memory-profile` enabled
+pub(crate) fn enable() {
+  ENABLED.with(|enabled| enabled.set(true));
+}
+
+/// Resets the high-water mark for the current thread, ready for the next test
+///
+/// Cheap to call unconditionally, even when this thread isn't tracking memory - it just
+/// clears an already-empty accumulator
+pub(crate) fn reset() {
+  TRACKER.with(|tracker| *tracker.borrow_mut() = MemoryTracker::default());
+}
+
+/// Gets the peak number of bytes allocated on the current thread since the last [`reset`],
+/// or `None` if this thread hasn't called [`enable`]
+pub(crate) fn peak() -> Option<usize> {
+  ENABLED.with(|enabled| enabled.get().then(|| TRACKER.with(|tracker| tracker.borrow().peak)))
+}
+
+/// Looks up the original allocator for the domain a tracking thunk was called for - recovered
+/// from `ctx`, which [`install`] set to that domain's index into [`ORIGINAL_ALLOCATORS`]
+fn original(ctx: *mut ffi::c_void) -> &'static ffi::PyMemAllocatorEx {
+  &ORIGINAL_ALLOCATORS.get().expect("allocator to be installed")[ctx as usize]
+}
+
+unsafe extern "C" fn tracking_malloc(ctx: *mut ffi::c_void, size: usize) -> *mut ffi::c_void {
+  let original = original(ctx);
+  let ptr = unsafe { original.malloc.unwrap()(original.ctx, size) };
+  TRACKER.with(|tracker| tracker.borrow_mut().record_alloc(ptr, size));
+  ptr
+}
+
+unsafe extern "C" fn tracking_calloc(
+  ctx: *mut ffi::c_void,
+  count: usize,
+  size: usize,
+) -> *mut ffi::c_void {
+  let original = original(ctx);
+  let ptr = unsafe { original.calloc.unwrap()(original.ctx, count, size) };
+  TRACKER.with(|tracker| tracker.borrow_mut().record_alloc(ptr, count * size));
+  ptr
+}
+
+unsafe extern "C" fn tracking_realloc(
+  ctx: *mut ffi::c_void,
+  ptr: *mut ffi::c_void,
+  size: usize,
+) -> *mut ffi::c_void {
+  let original = original(ctx);
+  TRACKER.with(|tracker| tracker.borrow_mut().record_free(ptr));
+
+  let new_ptr = unsafe { original.realloc.unwrap()(original.ctx, ptr, size) };
+  TRACKER.with(|tracker| tracker.borrow_mut().record_alloc(new_ptr, size));
+  new_ptr
+}
+
+unsafe extern "C" fn tracking_free(ctx: *mut ffi::c_void, ptr: *mut ffi::c_void) {
+  let original = original(ctx);
+  TRACKER.with(|tracker| tracker.borrow_mut().record_free(ptr));
+  unsafe { original.free.unwrap()(original.ctx, ptr) };
+}
@@ -1,11 +1,13 @@
+mod embedded;
 mod interpreters;
+mod memory;
 pub mod objects;
 mod operations;
 
 use pyo3_ffi::{self as ffi};
 use std::ffi::CStr;
 
-pub use interpreters::{Interpreter, MainInterpreter, SubInterpreter};
+pub use interpreters::{Interpreter, InterruptHandle, MainInterpreter, SubInterpreter};
 pub use objects::{PyError, PyObject};
 pub use operations::ActiveInterpreter;
 
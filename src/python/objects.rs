@@ -1,6 +1,8 @@
 use pyo3_ffi::{self as ffi};
 use std::{ffi::CStr, fmt, marker::PhantomData, ops::Deref, ptr::NonNull};
 
+use super::ActiveInterpreter;
+
 /// Represents a Python object
 pub struct PyObject(NonNull<ffi::PyObject>);
 impl PyObject {
@@ -33,7 +35,14 @@ impl PyObject {
     unsafe { ffi::PyObject_HasAttr(self.as_ptr(), attribute.as_ptr()) == 1 }
   }
   /// Get the attribute of an object
-  pub fn get_attr(&self, attribute: &PyObject) -> Result<PyObject, PyError> {
+  ///
+  /// Takes `&ActiveInterpreter` so holding the GIL is proven by the type system, rather
+  /// than assumed
+  pub fn get_attr(
+    &self,
+    _python: &ActiveInterpreter,
+    attribute: &PyObject,
+  ) -> Result<PyObject, PyError> {
     let result = unsafe { ffi::PyObject_GetAttr(self.as_ptr(), attribute.as_ptr()) };
 
     Self::from_ptr_or_error(result)
@@ -54,7 +63,12 @@ impl PyObject {
   }
   /// Set the attribute of an object
   #[expect(clippy::needless_pass_by_value, reason = "we want to take ownership")]
-  pub fn set_attr(&self, attribute: &PyObject, value: PyObject) -> Result<(), PyError> {
+  pub fn set_attr(
+    &self,
+    _python: &ActiveInterpreter,
+    attribute: &PyObject,
+    value: PyObject,
+  ) -> Result<(), PyError> {
     let result =
       unsafe { ffi::PyObject_SetAttr(self.as_ptr(), attribute.as_ptr(), value.as_ptr()) };
 
@@ -66,7 +80,7 @@ impl PyObject {
   }
 
   /// Calls the given object with no parameters
-  pub fn call(&self) -> Result<PyObject, PyError> {
+  pub fn call(&self, _python: &ActiveInterpreter) -> Result<PyObject, PyError> {
     // No debug assert against being callable, as would crash if the test is not a function
 
     let ptr = unsafe { ffi::PyObject_CallNoArgs(self.as_ptr()) };
@@ -82,6 +96,14 @@ impl PyObject {
     unsafe { Self::from_ptr_unchecked(ptr) }
   }
 
+  /// Calls the given object, passing the given tuple as positional arguments
+  pub fn call_with_args(&self, args: &PyTuple) -> Result<PyObject, PyError> {
+    // No debug assert against being callable, as would crash if the test is not a function
+
+    let ptr = unsafe { ffi::PyObject_CallObject(self.as_ptr(), args.as_ptr()) };
+    Self::from_ptr(ptr).ok_or_else(PyError::get)
+  }
+
   /// Convert the object to an iterator
   ///
   /// SAFETY: Assumes that the object is an iterator
@@ -89,12 +111,32 @@ impl PyObject {
     clippy::wrong_self_convention,
     reason = "works better with borrowed objects"
   )]
-  pub fn into_iter(&self) -> PyIter {
+  pub fn into_iter(&self, _python: &ActiveInterpreter) -> PyIter {
     let iterator_ptr = unsafe { ffi::PyObject_GetIter(self.as_ptr()) };
     debug_assert!(!iterator_ptr.is_null());
     PyIter(unsafe { Self::from_ptr_unchecked(iterator_ptr) })
   }
 
+  /// Convert the object to an iterator, or `None` if it isn't iterable
+  ///
+  /// Unlike [`Self::into_iter`], this is safe to call on an object of unknown type - e.g. a
+  /// scalar `@pytest.mark.parametrize` value, which may or may not be iterable depending on
+  /// how many parameter names the mark declared
+  #[expect(
+    clippy::wrong_self_convention,
+    reason = "works better with borrowed objects"
+  )]
+  pub fn try_into_iter(&self, _python: &ActiveInterpreter) -> Option<PyIter> {
+    let iterator_ptr = unsafe { ffi::PyObject_GetIter(self.as_ptr()) };
+
+    if iterator_ptr.is_null() {
+      unsafe { ffi::PyErr_Clear() };
+      return None;
+    }
+
+    Some(PyIter(unsafe { Self::from_ptr_unchecked(iterator_ptr) }))
+  }
+
   /// The name of the Type of the `PyObject`
   pub fn type_name(&self) -> String {
     let object_type = unsafe { ffi::Py_TYPE(self.as_ptr()) };
@@ -186,6 +228,25 @@ impl fmt::Display for PyObject {
     f.write_str(str)
   }
 }
+impl PyObject {
+  /// The `repr()` of the object, rather than its `str()` - e.g. a string's value is
+  /// wrapped in quotes, distinguishing it from the plain [`Display`](fmt::Display) form
+  pub fn repr(&self) -> String {
+    let string_object = unsafe { ffi::PyObject_Repr(self.as_ptr()) };
+
+    let mut size = 0;
+    let pointer = unsafe { ffi::PyUnicode_AsUTF8AndSize(string_object, &mut size) };
+
+    let Ok(length) = usize::try_from(size) else {
+      // There was an error by python in creating a string
+      return String::new();
+    };
+
+    // SAFETY: Python gives us a valid UTF-8 string
+    let slice = unsafe { std::slice::from_raw_parts(pointer.cast::<u8>(), length) };
+    unsafe { std::str::from_utf8_unchecked(slice) }.to_string()
+  }
+}
 
 /// A borrowed reference to a Python Object
 #[repr(transparent)]
@@ -230,7 +291,7 @@ impl PyDict {
   }
 
   /// Gets an item from a dictionary
-  pub fn get_item(&self, key: &PyObject) -> Option<BorrowedPyObject> {
+  pub fn get_item(&self, _python: &ActiveInterpreter, key: &PyObject) -> Option<BorrowedPyObject> {
     debug_assert!(self.is_dict());
 
     let ptr = unsafe { ffi::PyDict_GetItem(self.as_ptr(), key.as_ptr()) };
@@ -275,6 +336,20 @@ impl PyError {
       panic!("No exception has been raised");
     }
   }
+  /// Gets the currently raised exception, if one is set
+  ///
+  /// Mirrors PyO3's `PyErr::take`. Unlike [`PyError::get`], never panics - returns
+  /// `None` rather than assuming the caller is already on a known error path, so
+  /// it can be called speculatively to check for an exception left pending by a
+  /// call whose return value didn't report one
+  ///
+  /// SAFETY: Assumes that the GIL is held
+  pub fn take() -> Option<Self> {
+    let ptr = unsafe { ffi::PyErr_GetRaisedException() };
+
+    PyObject::from_ptr(ptr).map(Self)
+  }
+
   /// Clears an exception if one is set
   ///
   /// SAFETY: Assumes that the GIL is held
@@ -288,6 +363,28 @@ impl PyError {
 
     PyObject::from_ptr(traceback_ptr)
   }
+
+  /// Gets the exception's `__cause__` - the exception explicitly chained onto this
+  /// one with `raise ... from err`
+  pub fn get_cause(&self) -> Option<Self> {
+    let cause_ptr = unsafe { ffi::PyException_GetCause(self.as_ptr()) };
+
+    PyObject::from_ptr(cause_ptr).map(Self)
+  }
+  /// Gets the exception's `__context__` - the exception that was already being
+  /// handled when this one was raised
+  pub fn get_context(&self) -> Option<Self> {
+    let context_ptr = unsafe { ffi::PyException_GetContext(self.as_ptr()) };
+
+    PyObject::from_ptr(context_ptr).map(Self)
+  }
+  /// Whether `__context__` should be hidden, because it was cleared with
+  /// `raise ... from None`, or because an explicit `__cause__` already takes its place
+  pub fn suppresses_context(&self) -> bool {
+    self
+      .get_attr_cstr(c"__suppress_context__")
+      .is_ok_and(|suppress| suppress.is_truthy())
+  }
 }
 impl Deref for PyError {
   type Target = PyObject;
@@ -355,6 +452,20 @@ impl PyTuple {
     Self(object)
   }
 
+  /// Builds a new tuple from the given items, in order
+  pub fn new(items: Vec<PyObject>) -> Self {
+    let tuple = unsafe { ffi::PyTuple_New(items.len().try_into().unwrap()) };
+    let tuple = unsafe { PyObject::from_ptr_unchecked(tuple) };
+
+    for (index, item) in items.into_iter().enumerate() {
+      // `PyTuple_SetItem` steals the reference to `item`
+      unsafe { ffi::PyTuple_SetItem(tuple.as_ptr(), index.try_into().unwrap(), item.as_ptr()) };
+      std::mem::forget(item);
+    }
+
+    Self(tuple)
+  }
+
   /// Get the size of a tuple
   pub fn size(&self) -> isize {
     debug_assert!(self.is_tuple());
@@ -375,7 +486,11 @@ impl PyTuple {
     BorrowedPyObject::new(py_object)
   }
   /// Gets an item from a tuple
-  pub fn get_item(&self, index: isize) -> Result<BorrowedPyObject, PyError> {
+  pub fn get_item(
+    &self,
+    _python: &ActiveInterpreter,
+    index: isize,
+  ) -> Result<BorrowedPyObject, PyError> {
     debug_assert!(self.is_tuple());
     debug_assert!(index >= 0);
 
@@ -4,7 +4,7 @@ use std::{
   fs, io, path,
 };
 
-use super::{PyError, PyObject};
+use super::{memory, PyError, PyObject};
 
 /// Represents an interpreter with the GIL held, so we can perform actions on it
 pub struct ActiveInterpreter {
@@ -27,6 +27,36 @@ impl ActiveInterpreter {
     unsafe { PyObject::from_ptr_unchecked(result) }
   }
 
+  /// Creates a new Python integer from a Rust `i64`
+  pub fn new_int(&self, value: i64) -> PyObject {
+    let result = unsafe { ffi::PyLong_FromLongLong(value) };
+
+    unsafe { PyObject::from_ptr_unchecked(result) }
+  }
+
+  /// Creates a new Python float from a Rust `f64`
+  pub fn new_float(&self, value: f64) -> PyObject {
+    let result = unsafe { ffi::PyFloat_FromDouble(value) };
+
+    unsafe { PyObject::from_ptr_unchecked(result) }
+  }
+
+  /// Creates a new Python `bool`
+  pub fn new_bool(&self, value: bool) -> PyObject {
+    let result = unsafe { ffi::PyBool_FromLong(i64::from(value)) };
+
+    unsafe { PyObject::from_ptr_unchecked(result) }
+  }
+
+  /// Creates a new Python `bytes` object from a Rust byte slice
+  pub fn new_bytes(&self, bytes: &[u8]) -> PyObject {
+    // SAFETY: `bytes` has a valid length, and the pointer is valid
+    let length = bytes.len().try_into().unwrap();
+    let result = unsafe { ffi::PyBytes_FromStringAndSize(bytes.as_ptr().cast(), length) };
+
+    unsafe { PyObject::from_ptr_unchecked(result) }
+  }
+
   /// Imports a module
   ///
   /// SAFETY: Assumes that the module exists
@@ -47,45 +77,57 @@ impl ActiveInterpreter {
     let sys = self.import_module(c"sys");
     let io = self.import_module(c"io");
 
-    let string_io = io.get_attr(&self.new_string("StringIO")).unwrap();
+    let string_io = io.get_attr(self, &self.new_string("StringIO")).unwrap();
     let stdout_io = unsafe { string_io.call_unchecked() };
     let stderr_io = unsafe { string_io.call_unchecked() };
 
-    _ = sys.set_attr(&self.new_string("stdout"), stdout_io);
-    _ = sys.set_attr(&self.new_string("stderr"), stderr_io);
+    _ = sys.set_attr(self, &self.new_string("stdout"), stdout_io);
+    _ = sys.set_attr(self, &self.new_string("stderr"), stderr_io);
   }
 
   /// Get the captured stdout and stderr
   pub fn get_captured_output(&self) -> (Option<String>, Option<String>) {
     let sys = self.import_module(c"sys");
 
-    let stdout = sys.get_attr(&self.new_string("stdout")).unwrap();
-    let stderr = sys.get_attr(&self.new_string("stderr")).unwrap();
+    let stdout = sys.get_attr(self, &self.new_string("stdout")).unwrap();
+    let stderr = sys.get_attr(self, &self.new_string("stderr")).unwrap();
 
     let get_value_str = self.new_string("getvalue");
 
     // The user may have altered stdout/ stderr, or captured output may not be enabled
     let stdout_value = stdout
-      .get_attr(&get_value_str)
-      .and_then(|value| value.call())
+      .get_attr(self, &get_value_str)
+      .and_then(|value| value.call(self))
       .map(|x| x.to_string())
       .ok();
     let stderr_value = stderr
-      .get_attr(&get_value_str)
-      .and_then(|value| value.call())
+      .get_attr(self, &get_value_str)
+      .and_then(|value| value.call(self))
       .map(|x| x.to_string())
       .ok();
 
     (stdout_value, stderr_value)
   }
 
+  /// Resets the peak memory high-water mark for the current test, ready to attribute
+  /// whatever runs next to it - a no-op if `--memory-profile` isn't enabled
+  pub fn reset_memory_tracking(&self) {
+    memory::reset();
+  }
+
+  /// Gets the peak number of bytes allocated since [`Self::reset_memory_tracking`] was last
+  /// called, or `None` if `--memory-profile` isn't enabled for this subinterpreter
+  pub fn peak_memory(&self) -> Option<usize> {
+    memory::peak()
+  }
+
   /// Adds the given path to Python's module resolution path variable.
   ///
   /// Most commonly used to add the current folder to the module search path.
   /// Assumes Python Interpreter is currently active.
   pub fn add_to_sys_modules_path(&self, path: &CStr) {
     let sys = self.import_module(c"sys");
-    let path_list = sys.get_attr(&self.new_string("path")).unwrap();
+    let path_list = sys.get_attr(self, &self.new_string("path")).unwrap();
 
     unsafe {
       let path_string = ffi::PyUnicode_FromString(path.as_ptr());
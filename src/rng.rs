@@ -0,0 +1,77 @@
+//! A small, seedable PRNG (`xorshift64*`), good enough for mutation choices and test shuffling
+
+pub(crate) struct Rng(u64);
+impl Rng {
+  pub(crate) fn new(seed: u64) -> Self {
+    Self(seed | 1)
+  }
+
+  pub(crate) fn next(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+  }
+
+  pub(crate) fn below(&mut self, bound: usize) -> usize {
+    if bound == 0 {
+      0
+    } else {
+      (self.next() % bound as u64) as usize
+    }
+  }
+
+  /// Shuffles `items` in place using a Fisher-Yates shuffle
+  pub(crate) fn shuffle<T>(&mut self, items: &mut [T]) {
+    for index in (1..items.len()).rev() {
+      items.swap(index, self.below(index + 1));
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Rng;
+
+  #[test]
+  fn same_seed_shuffles_into_the_same_order() {
+    let mut a = Vec::from_iter(0..20);
+    let mut b = a.clone();
+
+    Rng::new(42).shuffle(&mut a);
+    Rng::new(42).shuffle(&mut b);
+
+    assert_eq!(a, b);
+  }
+
+  #[test]
+  fn different_seeds_shuffle_into_different_orders() {
+    let mut a = Vec::from_iter(0..20);
+    let mut b = a.clone();
+
+    Rng::new(1).shuffle(&mut a);
+    Rng::new(2).shuffle(&mut b);
+
+    assert_ne!(a, b);
+  }
+
+  #[test]
+  fn shuffle_is_a_permutation_of_the_original_items() {
+    let mut items = Vec::from_iter(0..20);
+    Rng::new(0xdead_beef).shuffle(&mut items);
+
+    let mut sorted = items.clone();
+    sorted.sort_unstable();
+
+    assert_eq!(sorted, Vec::from_iter(0..20));
+  }
+
+  #[test]
+  fn below_zero_is_always_zero() {
+    let mut rng = Rng::new(7);
+
+    for _ in 0..10 {
+      assert_eq!(rng.below(0), 0);
+    }
+  }
+}
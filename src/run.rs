@@ -1,14 +1,18 @@
 use crate::{
   discovery::Test,
+  expectations::{Expectations, RequiredOutcome},
   python::{
     objects::{PyDict, PyError, PyObject, PyTuple},
-    ActiveInterpreter,
+    ActiveInterpreter, Interpreter as _, InterruptHandle, MainInterpreter, SubInterpreter,
   },
 };
 
 use serde::{Deserialize, Serialize};
 use std::{
-  ops, path,
+  collections::{HashMap, HashSet},
+  fs, ops, path,
+  sync::{atomic::AtomicBool, atomic::Ordering, mpsc, Arc},
+  thread,
   time::{Duration, Instant},
 };
 
@@ -17,23 +21,118 @@ use std::{
 pub struct TestOutcome<'tests> {
   test: &'tests Test,
   pub outcome: OutcomeKind,
+  /// Peak bytes allocated whilst running the test, if memory tracking was enabled
+  peak_memory: Option<usize>,
+  /// The `[...]` id of this case, if `test` is expanded from `@pytest.mark.parametrize`
+  case: Option<String>,
+}
+impl<'tests> TestOutcome<'tests> {
+  /// Builds the outcome of a fuzz campaign run against a parametrized test function
+  pub fn fuzzed(
+    test: &'tests Test,
+    time: Duration,
+    iterations: u32,
+    failure: Option<(Error, Vec<u8>)>,
+  ) -> Self {
+    Self {
+      test,
+      outcome: OutcomeKind::Fuzzed {
+        time,
+        iterations,
+        failure: failure.map(|(error, input)| FuzzFailure {
+          error,
+          input: format!("{input:?}"),
+        }),
+      },
+      peak_memory: None,
+      case: None,
+    }
+  }
+
+  /// The test this outcome belongs to, so it can be re-run for `--retries`
+  pub fn test(&self) -> &'tests Test {
+    self.test
+  }
 }
 impl TestOutcome<'_> {
+  /// Attaches the peak memory usage recorded whilst running the test
+  #[must_use]
+  pub fn with_peak_memory(mut self, peak_memory: Option<usize>) -> Self {
+    self.peak_memory = peak_memory;
+    self
+  }
+
+  /// Peak bytes allocated whilst running the test, if memory tracking was enabled
+  pub fn peak_memory(&self) -> Option<usize> {
+    self.peak_memory
+  }
+
+  /// The `@pytest.mark.parametrize` case id this outcome belongs to, if any
+  pub fn case(&self) -> Option<&str> {
+    self.case.as_deref()
+  }
+
+  /// The test's identifier, suffixed with its parametrize case id if this outcome
+  /// belongs to one - e.g. `test_add[2-3-5]`
+  pub fn identifier(&self) -> String {
+    match &self.case {
+      Some(case) => format!("{}[{case}]", self.test.identifier()),
+      None => self.test.identifier(),
+    }
+  }
+
   pub fn time(&self) -> Option<Duration> {
     match self.outcome {
       OutcomeKind::Pass { time }
       | OutcomeKind::Fail { time, .. }
       | OutcomeKind::Error { time, .. }
-      | OutcomeKind::ExpectedFailure { time } => Some(time),
+      | OutcomeKind::ExpectedFailure { time }
+      | OutcomeKind::Busted { time }
+      | OutcomeKind::UnexpectedPass { time }
+      | OutcomeKind::Flaky { time, .. }
+      | OutcomeKind::Fuzzed { time, .. } => Some(time),
       _ => None,
     }
   }
 
   pub fn is_fail(&self) -> bool {
-    !matches!(
-      self.outcome,
-      OutcomeKind::Pass { .. } | OutcomeKind::Skip { .. }
-    )
+    match self.outcome {
+      OutcomeKind::Pass { .. }
+      | OutcomeKind::Skip { .. }
+      | OutcomeKind::Busted { .. }
+      | OutcomeKind::Flaky { .. } => false,
+      OutcomeKind::Fuzzed { ref failure, .. } => failure.is_some(),
+      _ => true,
+    }
+  }
+
+  /// Turns a failing outcome into a `Flaky` one, recording that it passed after `attempts`
+  /// retries under `--retries`
+  #[must_use]
+  pub fn into_flaky(mut self, attempts: u32) -> Self {
+    let time = self.time().unwrap_or_default();
+    self.outcome = OutcomeKind::Flaky { time, attempts };
+    self
+  }
+
+  /// Reconciles this outcome against an externally configured expectation manifest: a
+  /// genuinely failing/erroring test that's listed is downgraded to a non-fatal `Busted`
+  /// outcome, while a listed test that unexpectedly passes becomes a new `UnexpectedPass`
+  /// failure, so that drift between the manifest and reality is never silently hidden
+  #[must_use]
+  pub fn reconcile_expectation(mut self, expectations: &Expectations) -> Self {
+    let Some(required) = expectations.get(&self.identifier()) else {
+      return self;
+    };
+
+    self.outcome = match (required, self.outcome) {
+      (RequiredOutcome::Fail, OutcomeKind::Fail { time, .. })
+      | (RequiredOutcome::Error, OutcomeKind::Error { time, .. }) => OutcomeKind::Busted { time },
+      (_, OutcomeKind::Pass { time }) => OutcomeKind::UnexpectedPass { time },
+      (_, other) => other,
+    };
+
+    self
   }
 
   pub fn error(&self) -> Option<&Error> {
@@ -41,6 +140,7 @@ impl TestOutcome<'_> {
       OutcomeKind::Fail { error, .. }
       | OutcomeKind::Error { error, .. }
       | OutcomeKind::ModuleError { error } => Some(error),
+      OutcomeKind::Fuzzed { failure, .. } => failure.as_ref().map(|failure| &failure.error),
       _ => None,
     }
   }
@@ -53,6 +153,20 @@ impl ops::Deref for TestOutcome<'_> {
   }
 }
 
+/// Builds a [`TestOutcome`] for one case of `test`, naming its parametrize case id if any
+fn case_outcome<'test>(
+  test: &'test Test,
+  case: Option<String>,
+  outcome: OutcomeKind,
+) -> TestOutcome<'test> {
+  TestOutcome {
+    test,
+    outcome,
+    peak_memory: None,
+    case,
+  }
+}
+
 /// The different outcomes of running a test
 #[derive(Debug, Clone)]
 pub enum OutcomeKind {
@@ -68,13 +182,35 @@ pub enum OutcomeKind {
   ModuleError { error: Error },
   /// Expected the test to fail but it succeeded
   ExpectedFailure { time: Duration },
+  /// Listed in the expectations manifest, and failed/errored as required - a non-fatal outcome
+  Busted { time: Duration },
+  /// Listed in the expectations manifest, but passed when a failure/error was required
+  UnexpectedPass { time: Duration },
   /// Couldn't find test (likely due to static test def being changed at runtime)
   TestNotFound,
+  /// The test did not complete within the configured `--timeout`, and was interrupted
+  Timeout,
+  /// A coverage-guided fuzz campaign ran against a parametrized test function
+  Fuzzed {
+    time: Duration,
+    iterations: u32,
+    /// The minimized failing input, if the fuzzer found one
+    failure: Option<FuzzFailure>,
+  },
+  /// The test failed at least once, but passed after being re-run under `--retries`
+  Flaky { time: Duration, attempts: u32 },
+}
+
+/// A minimized input which reproduces a failure found whilst fuzzing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FuzzFailure {
+  pub error: Error,
+  pub input: String,
 }
 impl OutcomeKind {
-  pub fn module_error(error: PyError) -> Self {
+  pub fn module_error(python: &ActiveInterpreter, error: PyError) -> Self {
     Self::ModuleError {
-      error: error.into(),
+      error: Error::from_py_error(python, error),
     }
   }
 }
@@ -89,6 +225,17 @@ pub struct Error {
 
   pub stdout: Option<String>,
   pub stderr: Option<String>,
+
+  /// The exception this one was raised whilst handling, if any - forms a chain
+  /// analogous to Python's own "The above exception was the direct cause of ..."
+  /// rendering
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cause: Option<Box<Error>>,
+  /// Whether `cause` is the exception's explicit `__cause__` (`raise ... from err`),
+  /// as opposed to its implicit `__context__` (the exception already being handled
+  /// when this one was raised)
+  #[serde(default)]
+  pub explicit_cause: bool,
 }
 impl Error {
   pub fn is_assertion_error(&self) -> bool {
@@ -97,20 +244,52 @@ impl Error {
   pub fn is_skip_exception(&self) -> bool {
     self.kind.starts_with("Skip")
   }
-}
-impl From<PyError> for Error {
-  fn from(error: PyError) -> Self {
-    // SAFETY: assume that the GIl is held
-    let interpreter = unsafe { ActiveInterpreter::new() };
-    let (stdout, stderr) = interpreter.get_captured_output();
+
+  /// Builds an [`Error`] from a raised [`PyError`]
+  ///
+  /// Takes `&ActiveInterpreter` so fetching the captured output doesn't need to fabricate
+  /// one, proving the GIL is held rather than assuming it
+  pub fn from_py_error(python: &ActiveInterpreter, error: PyError) -> Self {
+    let (stdout, stderr) = python.get_captured_output();
+
+    Self {
+      stdout,
+      stderr,
+      ..Self::from_py_error_without_output(python, &error, &mut HashSet::new())
+    }
+  }
+
+  /// Builds an [`Error`] and its `cause`/`__context__` chain, without capturing
+  /// stdout/stderr - those belong to the test as a whole, not to each link in the chain
+  ///
+  /// `visited` tracks the identity of every exception already walked in this chain - CPython
+  /// allows `__context__` to cycle back on itself (its own traceback printer guards against
+  /// this too), so without it a cyclic chain would recurse until the stack overflows
+  fn from_py_error_without_output(
+    python: &ActiveInterpreter,
+    error: &PyError,
+    visited: &mut HashSet<usize>,
+  ) -> Self {
+    visited.insert(error.as_ptr() as usize);
+
+    let (cause, explicit_cause) = match error.get_cause() {
+      Some(cause) => (Some(cause), true),
+      None if !error.suppresses_context() => (error.get_context(), false),
+      None => (None, false),
+    };
+    let cause = cause.filter(|cause| !visited.contains(&(cause.as_ptr() as usize)));
 
     Self {
       kind: error.type_name(),
       message: error.to_string(),
 
-      traceback: Traceback::from(&error),
-      stdout,
-      stderr,
+      traceback: Traceback::from(python, error),
+      stdout: None,
+      stderr: None,
+
+      cause: cause
+        .map(|cause| Box::new(Self::from_py_error_without_output(python, &cause, visited))),
+      explicit_cause,
     }
   }
 }
@@ -119,27 +298,31 @@ pub struct Traceback {
   pub frames: Vec<TracebackFrame>,
 }
 impl Traceback {
-  fn from(error: &PyError) -> Option<Self> {
+  fn from(python: &ActiveInterpreter, error: &PyError) -> Option<Self> {
     let mut traceback = Self { frames: Vec::new() };
-    traceback.add_frame(&error.get_traceback()?).ok()?;
+    traceback.add_frame(python, &error.get_traceback()?).ok()?;
     Some(traceback)
   }
 
-  fn add_frame(&mut self, frame: &PyObject) -> Result<(), PyError> {
-    let code_object = frame.get_attr_cstr(c"tb_frame")?.get_attr_cstr(c"f_code")?;
+  fn add_frame(&mut self, python: &ActiveInterpreter, frame: &PyObject) -> Result<(), PyError> {
+    let tb_frame = frame.get_attr_cstr(c"tb_frame")?;
+    let code_object = tb_frame.get_attr_cstr(c"f_code")?;
 
     let line = frame.get_attr_cstr(c"tb_lineno")?.as_long();
     let function = code_object.get_attr_cstr(c"co_name")?.to_string();
     let file = code_object.get_attr_cstr(c"co_filename")?.to_string();
+    let file = path::PathBuf::from(file);
 
     self.frames.push(TracebackFrame {
+      source: read_source_context(&file, line),
+      locals: read_locals(python, &tb_frame),
       line,
       function,
-      file: path::PathBuf::from(file),
+      file,
     });
 
     if let Ok(frame) = frame.get_attr_cstr(c"tb_next") {
-      _ = self.add_frame(&frame);
+      _ = self.add_frame(python, &frame);
     }
 
     Ok(())
@@ -151,112 +334,633 @@ pub struct TracebackFrame {
   pub line: i32,
   pub function: String,
   pub file: path::PathBuf,
+
+  /// The source line `line` points at, with a couple of lines of surrounding context -
+  /// `None` if the file couldn't be read (e.g. it no longer exists on disk)
+  pub source: Option<String>,
+  /// The frame's local variables at the point it raised, as `(name, repr(value))` pairs
+  #[serde(default)]
+  pub locals: Vec<(String, String)>,
 }
 
-/// Executes the test as described by the [`Test`]
-pub fn test<'test>(python: &ActiveInterpreter, test: &'test Test) -> TestOutcome<'test> {
-  TestOutcome {
-    test,
-    outcome: match test {
-      Test::Function { .. } => test_function(python, test),
-      Test::Method { .. } => test_method(python, test),
-    },
+/// How many lines of source either side of the offending line to include as context
+const SOURCE_CONTEXT_LINES: usize = 2;
+
+/// Reads the source lines around `line` from `file`, for showing the code a frame pointed
+/// at alongside its traceback entry
+fn read_source_context(file: &path::Path, line: i32) -> Option<String> {
+  let source = fs::read_to_string(file).ok()?;
+  let lines: Vec<&str> = source.lines().collect();
+
+  let index = usize::try_from(line - 1).ok()?;
+  let start = index.saturating_sub(SOURCE_CONTEXT_LINES);
+  let end = (index + SOURCE_CONTEXT_LINES + 1).min(lines.len());
+
+  lines.get(start..end).map(|context| context.join("\n"))
+}
+
+/// Reads a frame's local variables, rendering each value via `repr()` so e.g. a string
+/// shows its quotes - the same way a debugger would
+fn read_locals(python: &ActiveInterpreter, tb_frame: &PyObject) -> Vec<(String, String)> {
+  let Ok(locals) = tb_frame.get_attr_cstr(c"f_locals") else {
+    return Vec::new();
+  };
+  let Some(locals) = PyDict::from_object(locals) else {
+    return Vec::new();
+  };
+
+  locals
+    .items()
+    .into_iter(python)
+    .map(|item| {
+      let item = unsafe { PyTuple::from_object_unchecked(item) };
+      let name = unsafe { item.get_item_unchecked(0) }.to_string();
+      let value = unsafe { item.get_item_unchecked(1) }.repr();
+
+      (name, value)
+    })
+    .collect()
+}
+
+/// One independently-schedulable unit of work - a single fuzz campaign (which manages its
+/// own subinterpreter in [`crate::fuzz::run`]), or a batch of tests sharing a file, so that
+/// `setUpModule`/`setUpClass` can be run once around the whole batch rather than per test
+pub enum Unit<'test> {
+  Fuzz(&'test Test),
+  Group(Vec<&'test Test>),
+}
+
+/// Splits discovered tests into schedulable [`Unit`]s - fuzz targets run individually, and
+/// everything else is grouped by file, preserving discovery order, so that module- and
+/// class-level fixtures shared between the grouped tests run exactly once
+pub fn schedule<'test>(tests: &'test [Test], fuzz_enabled: bool) -> Vec<Unit<'test>> {
+  let mut units: Vec<Unit<'test>> = Vec::new();
+  let mut group_index: HashMap<&path::Path, usize> = HashMap::new();
+
+  for test in tests {
+    if fuzz_enabled && !test.parameters().is_empty() {
+      units.push(Unit::Fuzz(test));
+      continue;
+    }
+
+    match group_index.get(test.file()) {
+      Some(&index) => {
+        let Unit::Group(group) = &mut units[index] else {
+          unreachable!("`group_index` only ever points at a `Unit::Group`")
+        };
+        group.push(test);
+      }
+      None => {
+        group_index.insert(test.file(), units.len());
+        units.push(Unit::Group(vec![test]));
+      }
+    }
   }
+
+  units
 }
 
-fn test_method(python: &ActiveInterpreter, test: &Test) -> OutcomeKind {
-  let start_time = Instant::now();
-  let module = match python.execute_file(test.file()) {
-    Ok(module) => module,
-    Err(error) => return OutcomeKind::module_error(error),
+/// Executes every test in `tests` (all from the same file) against a single subinterpreter -
+/// the module is executed once, `setUpModule`/`tearDownModule` run once around the whole
+/// group, and tests are further grouped by class so `setUpClass`/`tearDownClass` run once
+/// around each class's tests rather than once per test. A broken `setUpModule` fails every
+/// test it covers with a [`OutcomeKind::ModuleError`], rather than silently skipping them - a
+/// broken `tearDownModule` instead appends a synthetic outcome of its own, since by that
+/// point the real per-test outcomes have already been computed and shouldn't be discarded
+pub fn test_group<'test>(python: &ActiveInterpreter, tests: &[&'test Test]) -> Vec<TestOutcome<'test>> {
+  let Some(&first_test) = tests.first() else {
+    return Vec::new();
   };
 
-  let suite_name = python.new_string(test.suite().unwrap());
-  let Ok(class) = module.get_attr(&suite_name) else {
-    return OutcomeKind::TestNotFound;
+  let module = match python.execute_file(first_test.file()) {
+    Ok(module) => module,
+    Err(error) => return broadcast_module_error(python, tests, error),
   };
-  let class_instance = match class.call() {
+
+  if let Err(error) = call_optional_method(python, &module, "setUpModule") {
+    return broadcast_module_error(python, tests, error);
+  }
+
+  let mut outcomes: Vec<TestOutcome<'test>> = group_by_class(tests)
+    .into_iter()
+    .flat_map(|(class, class_tests)| test_class_group(python, &module, class, class_tests))
+    .collect();
+
+  if let Err(error) = call_optional_method(python, &module, "tearDownModule") {
+    outcomes.push(teardown_failure_outcome(python, tests, "tearDownModule", error));
+  }
+
+  outcomes
+}
+
+/// Executes every test in `tests` (all from the same file), enforcing a `--timeout`
+///
+/// A watchdog thread waits for `timeout` to elapse, then asynchronously raises `SystemExit`
+/// in the subinterpreter backing `interrupt_handle`, via [`InterruptHandle::interrupt`]. If
+/// the group finishes first, the watchdog is cancelled before it ever fires. The watchdog can
+/// only observe whether it fired before the group returned, not whether the raised exception
+/// is what actually stopped it - so a case is only reported as [`OutcomeKind::Timeout`] when
+/// the watchdog fired *and* that case came back as an error, its most likely outcome.
+///
+/// Grouping tests that share `setUpModule`/`setUpClass` onto one subinterpreter means the
+/// timeout now bounds the whole group rather than a single test - there's no longer a
+/// per-test subinterpreter of its own to interrupt independently
+pub fn test_group_with_timeout<'test>(
+  python: &ActiveInterpreter,
+  tests: &[&'test Test],
+  interrupt_handle: InterruptHandle,
+  timeout: Duration,
+) -> Vec<TestOutcome<'test>> {
+  let (cancel, cancelled) = mpsc::channel::<()>();
+  let timed_out = Arc::new(AtomicBool::new(false));
+
+  let watchdog = thread::spawn({
+    let timed_out = Arc::clone(&timed_out);
+    move || {
+      if cancelled.recv_timeout(timeout).is_err() {
+        timed_out.store(true, Ordering::SeqCst);
+        interrupt_handle.interrupt();
+      }
+    }
+  });
+
+  let mut outcomes = self::test_group(python, tests);
+
+  _ = cancel.send(());
+  _ = watchdog.join();
+
+  if timed_out.load(Ordering::SeqCst) {
+    for outcome in &mut outcomes {
+      if matches!(outcome.outcome, OutcomeKind::Error { .. }) {
+        outcome.outcome = OutcomeKind::Timeout;
+      }
+    }
+  }
+
+  outcomes
+}
+
+/// Re-runs every failing outcome in `outcomes` up to `retries` times, promoting one to
+/// [`OutcomeKind::Flaky`] as soon as a re-run passes rather than leaving it reported as a
+/// failure - a no-op when `retries` is `0`
+///
+/// Each retry runs `test` alone in a fresh [`SubInterpreter`], the same isolation
+/// [`crate::fuzz::run`] gives each of its iterations, so a pass on retry reflects the test
+/// itself, not leftover state from the original run or an earlier attempt
+pub fn retry_failures<'test>(
+  interpreter: &MainInterpreter,
+  retries: u32,
+  outcomes: Vec<TestOutcome<'test>>,
+) -> Vec<TestOutcome<'test>> {
+  if retries == 0 {
+    return outcomes;
+  }
+
+  outcomes
+    .into_iter()
+    .map(|outcome| retry_outcome(interpreter, retries, outcome))
+    .collect()
+}
+
+/// Retries a single failing outcome - returns it unchanged if it isn't a failure, or if it's
+/// still failing once `retries` attempts are exhausted
+fn retry_outcome<'test>(
+  interpreter: &MainInterpreter,
+  retries: u32,
+  outcome: TestOutcome<'test>,
+) -> TestOutcome<'test> {
+  if !outcome.is_fail() {
+    return outcome;
+  }
+
+  for attempt in 1..=retries {
+    if rerun_passes(interpreter, outcome.test(), outcome.case()) {
+      return outcome.into_flaky(attempt);
+    }
+  }
+
+  outcome
+}
+
+/// Re-runs `test` alone in a fresh subinterpreter and reports whether `case` (or the whole
+/// test, if it isn't a parametrize case) passed this time
+fn rerun_passes(interpreter: &MainInterpreter, test: &Test, case: Option<&str>) -> bool {
+  let mut subinterpreter = SubInterpreter::new(interpreter);
+
+  let outcomes = subinterpreter.with_gil(|python| {
+    python.capture_output();
+    python.add_parent_module_to_path(test.file());
+    test_group(python, &[test])
+  });
+
+  outcomes
+    .iter()
+    .find(|outcome| outcome.case() == case)
+    .is_some_and(|outcome| !outcome.is_fail())
+}
+
+/// Groups `tests` by the class they belong to, preserving first-seen order - tests with no
+/// class (plain functions and doctests) are each kept in their own single-test group, since
+/// they have no `setUpClass`/`tearDownClass` to share
+fn group_by_class<'test>(tests: &[&'test Test]) -> Vec<(Option<&'test str>, Vec<&'test Test>)> {
+  let mut groups: Vec<(Option<&'test str>, Vec<&'test Test>)> = Vec::new();
+  let mut group_index: HashMap<Option<&str>, usize> = HashMap::new();
+
+  for &test in tests {
+    let class = test.suite();
+
+    match class.and_then(|class| group_index.get(Some(class)).copied()) {
+      Some(index) => groups[index].1.push(test),
+      None if class.is_none() => groups.push((None, vec![test])),
+      None => {
+        group_index.insert(class, groups.len());
+        groups.push((class, vec![test]));
+      }
+    }
+  }
+
+  groups
+}
+
+/// Builds a [`OutcomeKind::ModuleError`] outcome, from `error`, shared by every test in
+/// `tests` - used when a fixture shared between them (module/class execution or setup)
+/// fails, so the whole group it was meant to protect is marked broken rather than silently
+/// skipped
+fn broadcast_module_error<'test>(
+  python: &ActiveInterpreter,
+  tests: &[&'test Test],
+  error: PyError,
+) -> Vec<TestOutcome<'test>> {
+  let outcome = OutcomeKind::module_error(python, error);
+
+  tests
+    .iter()
+    .map(|&test| case_outcome(test, None, outcome.clone()))
+    .collect()
+}
+
+/// Builds a synthetic [`TestOutcome`] reporting that a teardown fixture (`tearDownModule` or
+/// `tearDownClass`) raised `error` - added alongside the group's real per-test outcomes
+/// rather than replacing them, since a teardown failure says nothing about whether the tests
+/// it ran after actually passed
+fn teardown_failure_outcome<'test>(
+  python: &ActiveInterpreter,
+  tests: &[&'test Test],
+  fixture_name: &str,
+  error: PyError,
+) -> TestOutcome<'test> {
+  let &first_test = tests.first().expect("teardown only runs after a non-empty group");
+  let outcome = OutcomeKind::module_error(python, error);
+
+  case_outcome(first_test, Some(fixture_name.to_string()), outcome)
+}
+
+/// Runs `test` - a class method, and all its parametrize cases if any - against an
+/// already-resolved `class_object`, instantiating a fresh instance per case the same way
+/// `setUp`/`tearDown` are run per case, whilst `setUpClass`/`tearDownClass` stay shared
+fn test_class_method<'test>(
+  python: &ActiveInterpreter,
+  class_object: &PyObject,
+  test: &'test Test,
+) -> Vec<TestOutcome<'test>> {
+  let class_instance = match class_object.call(python) {
     Ok(class_instance) => class_instance,
-    Err(error) => return OutcomeKind::module_error(error),
+    Err(error) => return vec![case_outcome(test, None, OutcomeKind::module_error(python, error))],
   };
 
   if let Some(reason) = has_skip_annotation(python, &class_instance) {
-    return OutcomeKind::Skip { reason };
+    return vec![case_outcome(test, None, OutcomeKind::Skip { reason })];
   };
   let test_name = python.new_string(test.name());
-  let Ok(method) = class_instance.get_attr(&test_name) else {
-    return OutcomeKind::TestNotFound;
+  let Ok(method) = class_instance.get_attr(python, &test_name) else {
+    return vec![case_outcome(test, None, OutcomeKind::TestNotFound)];
   };
   if let Some(reason) = has_skip_annotation(python, &method) {
-    return OutcomeKind::Skip { reason };
+    return vec![case_outcome(test, None, OutcomeKind::Skip { reason })];
   }
 
   let expecting_failure = is_expecting_failure(python, &method);
-  if let Err(error) = call_optional_method(python, &class_instance, "setUp") {
-    return OutcomeKind::module_error(error);
+
+  match parametrize_cases(python, &method) {
+    None => {
+      let (outcome, peak_memory) =
+        run_method_case(python, &class_instance, &method, None, expecting_failure);
+      vec![case_outcome(test, None, outcome).with_peak_memory(peak_memory)]
+    }
+    Some(cases) => cases
+      .into_iter()
+      .map(|case| {
+        if let Some(reason) = check_skip_marks(python, case.marks.iter().cloned()) {
+          return case_outcome(test, Some(case.id), OutcomeKind::Skip { reason });
+        }
+
+        let case_instance = match class_object.call(python) {
+          Ok(case_instance) => case_instance,
+          Err(error) => {
+            return case_outcome(test, Some(case.id), OutcomeKind::module_error(python, error))
+          }
+        };
+        let Ok(case_method) = case_instance.get_attr(python, &test_name) else {
+          return case_outcome(test, Some(case.id), OutcomeKind::TestNotFound);
+        };
+
+        let expecting_failure = expecting_failure || check_xfail_marks(case.marks.iter().cloned());
+        let (outcome, peak_memory) = run_method_case(
+          python,
+          &case_instance,
+          &case_method,
+          Some(&case.args),
+          expecting_failure,
+        );
+
+        case_outcome(test, Some(case.id), outcome).with_peak_memory(peak_memory)
+      })
+      .collect(),
+  }
+}
+
+/// Resolves `class` within `module` and runs every method `tests` names on it, calling
+/// `setUpClass`/`tearDownClass` once around the whole group rather than per test. Tests with
+/// no class (`class` is `None`) are run directly against `module` instead. A broken
+/// `setUpClass` fails every method with a [`OutcomeKind::ModuleError`]; a broken
+/// `tearDownClass` instead appends a synthetic outcome alongside the already-computed
+/// per-method results, which are left untouched
+fn test_class_group<'test>(
+  python: &ActiveInterpreter,
+  module: &PyObject,
+  class: Option<&str>,
+  tests: Vec<&'test Test>,
+) -> Vec<TestOutcome<'test>> {
+  let Some(class_name) = class else {
+    return tests
+      .into_iter()
+      .flat_map(|test| test_module_level_test(python, module, test))
+      .collect();
   };
-  let test_result = method.call();
-  if let Err(error) = call_optional_method(python, &class_instance, "tearDown") {
-    return OutcomeKind::module_error(error);
+
+  let class_object = match module.get_attr(python, &python.new_string(class_name)) {
+    Ok(class_object) => class_object,
+    Err(_) => {
+      return tests
+        .into_iter()
+        .map(|test| case_outcome(test, None, OutcomeKind::TestNotFound))
+        .collect()
+    }
   };
-  let time = start_time.elapsed();
 
-  match test_result {
-    Ok(_) if expecting_failure => OutcomeKind::ExpectedFailure { time },
-    Err(_) if expecting_failure => OutcomeKind::Pass { time },
-    Ok(_) => OutcomeKind::Pass { time },
-    Err(error) => {
-      let error = Error::from(error);
+  if let Err(error) = call_optional_method(python, &class_object, "setUpClass") {
+    return broadcast_module_error(python, &tests, error);
+  }
 
-      if error.is_skip_exception() {
-        let reason = error.message;
-        OutcomeKind::Skip { reason }
-      } else if error.is_assertion_error() {
-        OutcomeKind::Fail { error, time }
-      } else {
-        OutcomeKind::Error { error, time }
-      }
+  let mut outcomes: Vec<TestOutcome<'test>> = tests
+    .iter()
+    .flat_map(|&test| test_class_method(python, &class_object, test))
+    .collect();
+
+  if let Err(error) = call_optional_method(python, &class_object, "tearDownClass") {
+    outcomes.push(teardown_failure_outcome(python, &tests, "tearDownClass", error));
+  }
+
+  outcomes
+}
+
+/// Runs `test` - a function or doctest with no enclosing class - against an
+/// already-executed `module`
+fn test_module_level_test<'test>(
+  python: &ActiveInterpreter,
+  module: &PyObject,
+  test: &'test Test,
+) -> Vec<TestOutcome<'test>> {
+  match test {
+    Test::Function { .. } => test_function(python, module, test),
+    Test::DocTest { .. } => {
+      let (outcome, peak_memory) = test_doctest(python, module, test);
+      vec![case_outcome(test, None, outcome).with_peak_memory(peak_memory)]
     }
+    Test::Method { .. } => unreachable!("methods are grouped by class, not run at module level"),
   }
 }
 
-fn test_function(python: &ActiveInterpreter, test: &Test) -> OutcomeKind {
+/// Runs one invocation of a resolved test method - `setUp`, the test body (passed `args`,
+/// if this is a parametrized case), then `tearDown` - checking after each phase for an
+/// exception Python left pending that the phase's own `call()` didn't report
+///
+/// The peak memory high-water mark is reset immediately before the test body is called, and
+/// read back immediately after, so the returned reading is attributed to this one case
+/// rather than shared with every other test run on the same subinterpreter
+fn run_method_case(
+  python: &ActiveInterpreter,
+  class_instance: &PyObject,
+  method: &PyObject,
+  args: Option<&PyTuple>,
+  expecting_failure: bool,
+) -> (OutcomeKind, Option<usize>) {
   let start_time = Instant::now();
-  let module = match python.execute_file(test.file()) {
-    Ok(module) => module,
-    Err(error) => return OutcomeKind::module_error(error),
+
+  if let Err(error) = call_optional_method(python, class_instance, "setUp") {
+    return (OutcomeKind::module_error(python, error), None);
   };
+  if let Some(outcome) = check_pending_exception(python, "setUp") {
+    return (outcome, None);
+  }
 
+  python.reset_memory_tracking();
+  let test_result = match args {
+    Some(args) => method.call_with_args(args),
+    None => method.call(python),
+  };
+  let peak_memory = python.peak_memory();
+  if let Some(outcome) = check_pending_exception(python, "the test") {
+    return (outcome, peak_memory);
+  }
+
+  if let Err(error) = call_optional_method(python, class_instance, "tearDown") {
+    return (OutcomeKind::module_error(python, error), peak_memory);
+  };
+  if let Some(outcome) = check_pending_exception(python, "tearDown") {
+    return (outcome, peak_memory);
+  }
+
+  let outcome = outcome_from_result(python, test_result, expecting_failure, start_time.elapsed());
+  (outcome, peak_memory)
+}
+
+/// Runs one invocation of a resolved test function, passed `args` if this is a
+/// parametrized case, checking afterwards for an exception left pending that `call()`
+/// didn't report
+///
+/// The peak memory high-water mark is reset immediately before the test body is called, and
+/// read back immediately after, so the returned reading is attributed to this one case
+/// rather than shared with every other test run on the same subinterpreter
+fn run_function_case(
+  python: &ActiveInterpreter,
+  function: &PyObject,
+  args: Option<&PyTuple>,
+  expecting_failure: bool,
+) -> (OutcomeKind, Option<usize>) {
+  let start_time = Instant::now();
+
+  python.reset_memory_tracking();
+  let test_result = match args {
+    Some(args) => function.call_with_args(args),
+    None => function.call(python),
+  };
+  let peak_memory = python.peak_memory();
+  if let Some(outcome) = check_pending_exception(python, "the test") {
+    return (outcome, peak_memory);
+  }
+
+  let outcome = outcome_from_result(python, test_result, expecting_failure, start_time.elapsed());
+  (outcome, peak_memory)
+}
+
+fn test_function<'test>(
+  python: &ActiveInterpreter,
+  module: &PyObject,
+  test: &'test Test,
+) -> Vec<TestOutcome<'test>> {
   let test_name = python.new_string(test.name());
-  let Ok(function) = module.get_attr(&test_name) else {
-    return OutcomeKind::TestNotFound;
+  let Ok(function) = module.get_attr(python, &test_name) else {
+    return vec![case_outcome(test, None, OutcomeKind::TestNotFound)];
   };
 
   if let Some(reason) = has_skip_annotation(python, &function) {
-    return OutcomeKind::Skip { reason };
+    return vec![case_outcome(test, None, OutcomeKind::Skip { reason })];
   }
 
   let expecting_failure = is_expecting_failure(python, &function);
-  let test_result = function.call();
+
+  match parametrize_cases(python, &function) {
+    None => {
+      let (outcome, peak_memory) = run_function_case(python, &function, None, expecting_failure);
+      vec![case_outcome(test, None, outcome).with_peak_memory(peak_memory)]
+    }
+    Some(cases) => cases
+      .into_iter()
+      .map(|case| {
+        if let Some(reason) = check_skip_marks(python, case.marks.iter().cloned()) {
+          return case_outcome(test, Some(case.id), OutcomeKind::Skip { reason });
+        }
+
+        let expecting_failure = expecting_failure || check_xfail_marks(case.marks.iter().cloned());
+        let (outcome, peak_memory) =
+          run_function_case(python, &function, Some(&case.args), expecting_failure);
+
+        case_outcome(test, Some(case.id), outcome).with_peak_memory(peak_memory)
+      })
+      .collect(),
+  }
+}
+
+/// Runs a single `>>>` doctest example, re-locating it by qualified name and line number
+///
+/// The `doctest.DocTest` object found during discovery can't be reused here, as it belongs
+/// to the interpreter that discovered it, not this test's `SubInterpreter`. The peak memory
+/// high-water mark is reset immediately before `runner.run` is called, and read back
+/// immediately after, so the returned reading is attributed to this one doctest rather than
+/// shared with every other test run on the same subinterpreter
+fn test_doctest(
+  python: &ActiveInterpreter,
+  module: &PyObject,
+  test: &Test,
+) -> (OutcomeKind, Option<usize>) {
+  let Test::DocTest {
+    qualified_name,
+    line,
+    ..
+  } = test
+  else {
+    unreachable!("`test_doctest` is only called for `Test::DocTest`")
+  };
+
+  let start_time = Instant::now();
+
+  let Some(doctest) = find_doctest(python, module, qualified_name, *line) else {
+    return (OutcomeKind::TestNotFound, None);
+  };
+
+  let doctest_module = python.import_module(c"doctest");
+  let runner = unsafe {
+    doctest_module
+      .get_attr_unchecked(&python.new_string("DocTestRunner"))
+      .call_unchecked()
+  };
+
+  python.reset_memory_tracking();
+  let run_result = runner
+    .get_attr(python, &python.new_string("run"))
+    .and_then(|run| run.call_with_args(&PyTuple::new(vec![doctest])));
+  let peak_memory = python.peak_memory();
   let time = start_time.elapsed();
 
-  match test_result {
-    Ok(_) if expecting_failure => OutcomeKind::ExpectedFailure { time },
-    Err(_) if expecting_failure => OutcomeKind::Pass { time },
-    Ok(_) => OutcomeKind::Pass { time },
+  let result = match run_result {
+    Ok(result) => result,
     Err(error) => {
-      let error = Error::from(error);
+      return (
+        OutcomeKind::Error {
+          error: Error::from_py_error(python, error),
+          time,
+        },
+        peak_memory,
+      )
+    }
+  };
 
-      if error.is_skip_exception() {
-        let reason = error.message;
-        OutcomeKind::Skip { reason }
-      } else if error.is_assertion_error() {
-        OutcomeKind::Fail { error, time }
-      } else {
-        OutcomeKind::Error { error, time }
-      }
+  let failed = result
+    .get_attr(python, &python.new_string("failed"))
+    .map(|failed| failed.as_long())
+    .unwrap_or(0);
+
+  let outcome = if failed > 0 {
+    let (stdout, stderr) = python.get_captured_output();
+
+    OutcomeKind::Fail {
+      error: Error {
+        kind: "DocTestFailure".to_string(),
+        message: format!("{qualified_name}: one or more examples did not match"),
+        traceback: None,
+        stdout,
+        stderr,
+        cause: None,
+        explicit_cause: false,
+      },
+      time,
     }
-  }
+  } else {
+    OutcomeKind::Pass { time }
+  };
+
+  (outcome, peak_memory)
+}
+
+/// Finds the `doctest.DocTest` matching `qualified_name`/`line` within an already-executed module
+fn find_doctest(
+  python: &ActiveInterpreter,
+  module: &PyObject,
+  qualified_name: &str,
+  line: i32,
+) -> Option<PyObject> {
+  let doctest_module = python.import_module(c"doctest");
+  let finder = unsafe {
+    doctest_module
+      .get_attr_unchecked(&python.new_string("DocTestFinder"))
+      .call_unchecked()
+  };
+  let found = finder
+    .get_attr(python, &python.new_string("find"))
+    .ok()?
+    .call_with_args(&PyTuple::new(vec![module.clone()]))
+    .ok()?;
+
+  found.into_iter(python).find(|doctest| {
+    let name = doctest.get_attr_cstr(c"name").ok().map(|name| name.to_string());
+    let lineno = doctest
+      .get_attr_cstr(c"lineno")
+      .ok()
+      .map(|lineno| lineno.as_long());
+
+    name.as_deref() == Some(qualified_name) && lineno == Some(line)
+  })
 }
 
 /// Checks a [`PyObject`] for the annotation to skip the test, and returns the set reason for skipping as a string
@@ -270,61 +974,196 @@ fn has_skip_annotation(python: &ActiveInterpreter, object: &PyObject) -> Option<
     return Some(reason);
   }
 
-  if let Ok(pytest_marks) = object.get_attr_cstr(c"pytestmark") {
-    for mark in pytest_marks.into_iter() {
-      let mark_name = mark.get_attr_cstr(c"name").ok()?.to_string();
+  let pytest_marks = object.get_attr_cstr(c"pytestmark").ok()?;
+  check_skip_marks(python, pytest_marks.into_iter(python))
+}
 
-      let should_skip = match mark_name.as_str() {
-        "skip" => true,
-        "skipIf" => unsafe {
-          PyTuple::from_object_unchecked(mark.get_attr_cstr(c"args").ok()?)
-            .get_item_unchecked(0)
-            .is_truthy()
-        },
-        _ => false,
-      };
+/// Checks a [`PyObject`] for the annotation for expecting a failure
+fn is_expecting_failure(python: &ActiveInterpreter, object: &PyObject) -> bool {
+  if has_truthy_attr(python, object, "__unittest_expecting_failure__") {
+    return true;
+  }
 
-      if should_skip {
-        let reason = if let Ok(kwargs) = mark.get_attr_cstr(c"kwargs") {
-          unsafe {
-            PyDict::from_object_unchecked(kwargs)
-              .get_item(&python.new_string("reason"))
-              .map(|item| item.to_string())
-              .unwrap_or_default()
-          }
-        } else {
-          String::new()
-        };
+  let Ok(pytest_marks) = object.get_attr_cstr(c"pytestmark") else {
+    return false;
+  };
+  check_xfail_marks(pytest_marks.into_iter(python))
+}
 
-        return Some(reason);
-      }
+/// Checks a sequence of pytest marks (a `pytestmark` list, or the `marks` attached to one
+/// `pytest.param(..., marks=...)` case) for a `skip`/`skipIf` mark, returning its reason
+fn check_skip_marks(
+  python: &ActiveInterpreter,
+  marks: impl Iterator<Item = PyObject>,
+) -> Option<String> {
+  for mark in marks {
+    let mark_name = mark.get_attr_cstr(c"name").ok()?.to_string();
+
+    let should_skip = match mark_name.as_str() {
+      "skip" => true,
+      "skipIf" => unsafe {
+        PyTuple::from_object_unchecked(mark.get_attr_cstr(c"args").ok()?)
+          .get_item_unchecked(0)
+          .is_truthy()
+      },
+      _ => false,
+    };
+
+    if should_skip {
+      let reason = if let Ok(kwargs) = mark.get_attr_cstr(c"kwargs") {
+        unsafe {
+          PyDict::from_object_unchecked(kwargs)
+            .get_item(python, &python.new_string("reason"))
+            .map(|item| item.to_string())
+            .unwrap_or_default()
+        }
+      } else {
+        String::new()
+      };
+
+      return Some(reason);
     }
   }
 
   None
 }
 
-/// Checks a [`PyObject`] for the annotation for expecting a failure
-fn is_expecting_failure(python: &ActiveInterpreter, object: &PyObject) -> bool {
-  if has_truthy_attr(python, object, "__unittest_expecting_failure__") {
-    return true;
+/// Checks a sequence of pytest marks (a `pytestmark` list, or the `marks` attached to one
+/// `pytest.param(..., marks=...)` case) for an `xfail` mark
+fn check_xfail_marks(marks: impl Iterator<Item = PyObject>) -> bool {
+  for mark in marks {
+    let mark_name = mark.get_attr_cstr(c"name").unwrap().to_string();
+
+    if mark_name == "xfail" {
+      return unsafe {
+        PyTuple::from_object_unchecked(mark.get_attr_cstr(c"args").unwrap())
+          .get_item_unchecked(0)
+          .is_truthy()
+      };
+    }
   }
 
-  if let Ok(pytest_marks) = object.get_attr_cstr(c"pytestmark") {
-    for mark in pytest_marks.into_iter() {
-      let mark_name = mark.get_attr_cstr(c"name").unwrap().to_string();
+  false
+}
 
-      if mark_name == "xfail" {
-        return unsafe {
-          PyTuple::from_object_unchecked(mark.get_attr_cstr(c"args").unwrap())
-            .get_item_unchecked(0)
-            .is_truthy()
-        };
+/// One expanded case of an `@pytest.mark.parametrize` test: the positional arguments to
+/// invoke the function/method with, the id to report it under (e.g. the `2-3-5` of
+/// `test_add[2-3-5]`), and any marks attached to this case via `pytest.param(..., marks=...)`
+struct ParametrizeCase {
+  id: String,
+  args: PyTuple,
+  marks: Vec<PyObject>,
+}
+
+/// Reads an `@pytest.mark.parametrize` mark off `object`'s `pytestmark`, expanding it into
+/// one [`ParametrizeCase`] per argument set. Returns `None` if the test isn't parametrized
+fn parametrize_cases(python: &ActiveInterpreter, object: &PyObject) -> Option<Vec<ParametrizeCase>> {
+  let pytest_marks = object.get_attr_cstr(c"pytestmark").ok()?;
+
+  let mark = pytest_marks.into_iter(python).find(|mark| {
+    mark
+      .get_attr_cstr(c"name")
+      .is_ok_and(|name| name.to_string() == "parametrize")
+  })?;
+
+  // `mark.args[0]` (`argnames`) is only read to tell whether a single parameter is named
+  // (`"n"`, or a one-item sequence) - values are bound to their names positionally
+  let args = unsafe { PyTuple::from_object_unchecked(mark.get_attr_cstr(c"args").ok()?) };
+  let argnames = unsafe { args.get_item_unchecked(0) };
+  let single_name = if argnames.type_name() == "str" {
+    !argnames.to_string().contains(',')
+  } else {
+    argnames.into_iter(python).count() == 1
+  };
+  let argvalues = unsafe { args.get_item_unchecked(1) };
+
+  Some(
+    argvalues
+      .into_iter(python)
+      .map(|value| parametrize_case(python, value, single_name))
+      .collect(),
+  )
+}
+
+/// Builds one [`ParametrizeCase`] from an `argvalues` entry, which is either a bare value (a
+/// tuple of values bound positionally, or - when the test takes a single parameter - that
+/// parameter's value directly) or a `pytest.param(...)` wrapping one with an explicit id
+/// and/or marks
+fn parametrize_case(python: &ActiveInterpreter, value: PyObject, single_name: bool) -> ParametrizeCase {
+  let (values, id, marks) = if let Ok(values) = value.get_attr_cstr(c"values") {
+    let id = value
+      .get_attr_cstr(c"id")
+      .ok()
+      .filter(|id| !id.is_none())
+      .map(|id| id.to_string());
+    let marks = value
+      .get_attr_cstr(c"marks")
+      .map(|marks| normalize_marks(python, marks))
+      .unwrap_or_default();
+
+    let values: Vec<PyObject> = values.into_iter(python).collect();
+    (values, id, marks)
+  } else {
+    // A single-parameter `@pytest.mark.parametrize("n", [1, 2, 3])` binds each value
+    // directly, rather than unpacking it - and that value need not be iterable at all (an
+    // `int` isn't), so it can't just be handed to `into_iter` like the tuple form can
+    let values = match value.try_into_iter(python) {
+      Some(values) if !single_name => values.collect(),
+      _ => vec![value],
+    };
+
+    (values, None, Vec::new())
+  };
+
+  let id = id.unwrap_or_else(|| {
+    values
+      .iter()
+      .map(ToString::to_string)
+      .collect::<Vec<_>>()
+      .join("-")
+  });
+
+  ParametrizeCase {
+    id,
+    args: PyTuple::new(values),
+    marks,
+  }
+}
+
+/// Normalizes a `pytest.param(..., marks=...)` value - a single mark, or a list/tuple of
+/// marks - into a flat list
+fn normalize_marks(python: &ActiveInterpreter, marks: PyObject) -> Vec<PyObject> {
+  if marks.has_attr(&python.new_string("name")) {
+    vec![marks]
+  } else {
+    marks.into_iter(python).collect()
+  }
+}
+
+/// Classifies the result of calling a test function/method into an [`OutcomeKind`]
+fn outcome_from_result(
+  python: &ActiveInterpreter,
+  test_result: Result<PyObject, PyError>,
+  expecting_failure: bool,
+  time: Duration,
+) -> OutcomeKind {
+  match test_result {
+    Ok(_) if expecting_failure => OutcomeKind::ExpectedFailure { time },
+    Err(_) if expecting_failure => OutcomeKind::Pass { time },
+    Ok(_) => OutcomeKind::Pass { time },
+    Err(error) => {
+      let error = Error::from_py_error(python, error);
+
+      if error.is_skip_exception() {
+        let reason = error.message;
+        OutcomeKind::Skip { reason }
+      } else if error.is_assertion_error() {
+        OutcomeKind::Fail { error, time }
+      } else {
+        OutcomeKind::Error { error, time }
       }
     }
   }
-
-  false
 }
 
 fn call_optional_method(
@@ -336,12 +1175,25 @@ fn call_optional_method(
 
   if object.has_attr(&method_name) {
     let method = unsafe { object.get_attr_unchecked(&method_name) };
-    let _call_result = method.call()?;
+    let _call_result = method.call(python)?;
   }
 
   Ok(())
 }
 
+/// Checks for an exception Python left set that the preceding phase's `call()` didn't
+/// report as an `Err` - e.g. a `ResourceWarning` escalated to an error during `tearDown`,
+/// or an exception raised inside a generator-based fixture after the test body already
+/// returned. Left unchecked, such an exception silently poisons the next test run in this
+/// subinterpreter, so each phase drains it here and attributes it to where it was found
+fn check_pending_exception(python: &ActiveInterpreter, phase: &str) -> Option<OutcomeKind> {
+  let error = PyError::take()?;
+  let mut error = Error::from_py_error(python, error);
+  error.message = format!("exception left pending after {phase}: {}", error.message);
+
+  Some(OutcomeKind::ModuleError { error })
+}
+
 fn has_truthy_attr(python: &ActiveInterpreter, object: &PyObject, attribute: &str) -> bool {
   let attribute = python.new_string(attribute);
 
@@ -351,5 +1203,5 @@ fn has_truthy_attr(python: &ActiveInterpreter, object: &PyObject, attribute: &st
     return false;
   }
 
-  object.get_attr(&attribute).unwrap().is_truthy()
+  object.get_attr(python, &attribute).unwrap().is_truthy()
 }
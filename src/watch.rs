@@ -0,0 +1,94 @@
+use crate::{config::Settings, discover_tests, output, python, python::MainInterpreter, run_tests};
+
+use notify::{
+  event::{Event as FileEvent, EventKind},
+  RecursiveMode, Watcher,
+};
+use std::{sync::mpsc, time::Duration};
+
+/// Runs tests once, then keeps watching `settings.paths` for source file changes,
+/// re-discovering and re-running every test after each change settles
+///
+/// The [`MainInterpreter`] is kept alive for the lifetime of the watch, but each iteration
+/// still gets fresh [`python::SubInterpreter`]s (via [`run_tests`]) so module state cached by
+/// `PyImport_ExecCodeModuleEx` in a previous iteration can never leak into the next one. The
+/// loop only ends if the file watcher's channel disconnects, at which point this returns and
+/// the [`MainInterpreter`] is dropped; no Ctrl-C handler is installed here.
+pub fn run(settings: &Settings) {
+  // `run_tests` exits the process on the first failure when `no_fail_fast` is unset - fine for
+  // a one-shot run, but it would kill the watcher after its first failing test. Force it off
+  // for every iteration here instead of leaking this quirk into `run_tests` itself.
+  let settings = &Settings {
+    no_fail_fast: true,
+    ..settings.clone()
+  };
+
+  let mut reporter = output::new_reporter(settings.output);
+  reporter.initialize(python::version());
+
+  let mut interpreter = MainInterpreter::initialize(settings.embedded_stdlib);
+  interpreter.with_gil(|python| {
+    // The decimal module crashes Python 3.12 if it is initialised multiple times
+    // If not initialised in the base interpreter, if a subinterpreter imports it it will crash
+    _ = python.import_module(c"decimal");
+  });
+
+  let (sender, receiver) = mpsc::channel();
+  let mut watcher = notify::recommended_watcher(move |event: notify::Result<FileEvent>| {
+    if let Ok(event) = event {
+      _ = sender.send(event);
+    }
+  })
+  .expect("file watcher to be created");
+
+  for path in &settings.paths {
+    if let Err(error) = watcher.watch(path, RecursiveMode::Recursive) {
+      eprintln!("Could not watch {}: {error}", path.display());
+    }
+  }
+
+  loop {
+    let discovered = discover_tests(settings, &interpreter, reporter.as_mut());
+    run_tests(settings, &interpreter, &discovered, reporter.as_mut());
+    eprintln!("\nWatching for file changes... (Ctrl-C to exit)");
+
+    if !wait_for_relevant_change(&receiver) {
+      return;
+    }
+  }
+}
+
+/// Blocks until a burst of file-system events settles, debouncing bursts for ~200ms.
+///
+/// Returns `false` once the watcher's channel disconnects, so the caller can stop watching.
+fn wait_for_relevant_change(receiver: &mpsc::Receiver<FileEvent>) -> bool {
+  loop {
+    let Ok(first) = receiver.recv() else {
+      return false;
+    };
+
+    let mut changes = vec![first];
+    while let Ok(event) = receiver.recv_timeout(Duration::from_millis(200)) {
+      changes.push(event);
+    }
+
+    if changes.iter().any(is_relevant_change) {
+      return true;
+    }
+  }
+}
+
+/// Filters out events which don't represent a meaningful change to a Python file's contents
+///
+/// `watcher.watch` is recursive over the whole of `settings.paths`, so without this, writes
+/// to unrelated files (a `.git` object, an editor swap file, `__pycache__`) would trigger a
+/// full re-discovery and re-run just as readily as an actual test change would
+fn is_relevant_change(event: &FileEvent) -> bool {
+  matches!(
+    event.kind,
+    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+  ) && event
+    .paths
+    .iter()
+    .any(|path| path.extension().is_some_and(|extension| extension == "py"))
+}
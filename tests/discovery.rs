@@ -10,7 +10,13 @@ fn count_tests_run(args: &[&str]) -> usize {
     .unwrap();
 
   let stdout = String::from_utf8(cmd_output.stdout).unwrap();
-  stdout.lines().count()
+
+  // The JSON reporter also emits a leading `discovered` and a trailing `summary` event
+  // alongside the per-test `result` events this is meant to count
+  stdout
+    .lines()
+    .filter(|line| line.contains("\"event\":\"result\""))
+    .count()
 }
 
 #[test]
@@ -7,13 +7,16 @@ macro_rules! execution_test {
     execution_test!($name, stringify!($name));
   };
   ($name:ident, $path:expr) => {
+    execution_test!($name, $path, []);
+  };
+  ($name:ident, $path:expr, [$($arg:expr),* $(,)?]) => {
     #[test]
     fn $name() {
       let test_file_path = concat!("./tests/execution/", $path, ".py");
       let test_file = include_str!(concat!("./execution/", $path, ".py"));
 
       let expected_results = expected_results(test_file);
-      let test_results = run_test(test_file_path);
+      let test_results = run_test(test_file_path, &[$($arg),*]);
 
       for (test_name, (outcome, expected_error)) in &expected_results {
         let Some(result) = test_results
@@ -104,6 +107,7 @@ fn expected_results(test_file: &str) -> HashMap<String, (Outcome, Option<ErrorAs
             "SKIP" => Outcome::Skip,
             "EXPECTED FAILURE" => Outcome::ExpectedFailure,
             "NON TEST FAIL" => Outcome::NonTestFail,
+            "FLAKY" => Outcome::Flaky,
             _ => panic!("Unknown Outcome: {}", outcome.trim()),
           },
           error,
@@ -132,6 +136,8 @@ pub enum Outcome {
   Skip,
   ExpectedFailure,
   NonTestFail,
+  /// Failed at least once, but passed after being re-run under `--retries`
+  Flaky,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -152,20 +158,24 @@ pub struct ErrorAssertion {
   // also has traceback field, but we don't test on that yet
 }
 
-fn run_test(test_path: &str) -> Vec<TestOutput> {
+fn run_test(test_path: &str, extra_args: &[&str]) -> Vec<TestOutput> {
   let cmd_output = Command::cargo_bin(env!("CARGO_PKG_NAME"))
     .unwrap()
     .arg(test_path)
     .arg("--output=json")
     .arg("--no-fail-fast")
+    .args(extra_args)
     .output()
     .unwrap();
 
   let stdout = String::from_utf8(cmd_output.stdout).unwrap();
 
+  // The JSON reporter also emits a leading `discovered` and a trailing `summary` event -
+  // neither has the fields `TestOutput` expects, so only the per-test `result` events are kept
   stdout
     .lines()
-    .map(|line| serde_json::from_str::<TestOutput>(&line).unwrap())
+    .filter(|line| line.contains("\"event\":\"result\""))
+    .map(|line| serde_json::from_str::<TestOutput>(line).unwrap())
     .collect::<Vec<TestOutput>>()
 }
 
@@ -178,9 +188,13 @@ execution_test!(imports);
 execution_test!(import_submodule, "package/import_submodule");
 execution_test!(import_decimal);
 execution_test!(invalid_code);
+execution_test!(parametrize_scalar);
 #[cfg(feature = "ci")] // Takes a long time, so don't want it slowing down developement cycles
 execution_test!(long_running);
 #[cfg(not(feature = "ci"))] // Pytest crashes in CI, with a double free error - don't know why
 execution_test!(pytest_marks);
 execution_test!(skip_tests);
+execution_test!(teardown_module_failure);
+execution_test!(timeout_mid_test, "timeout_mid_test", ["--timeout", "200"]);
+execution_test!(flaky_test, "flaky_test", ["--retries", "1"]);
 execution_test!(times); // No tests are in this file, just a standard python file